@@ -0,0 +1,44 @@
+use imgui::MouseButton;
+use std::collections::HashMap;
+
+/// Receives pointer buttons that are not bound to an imgui button, by their
+/// Linux button code (e.g. `BTN_TASK`, `0x117`), and whether they are pressed.
+pub type ButtonCallback = Box<dyn FnMut(u32, bool)>;
+
+/// Buttons beyond the five imgui knows about, as found on gaming mice.
+pub(crate) struct ExtraButtons {
+    bindings: HashMap<u32, MouseButton>,
+    callback: Option<ButtonCallback>,
+}
+impl ExtraButtons {
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            callback: None,
+        }
+    }
+
+    pub fn bind(&mut self, code: u32, button: MouseButton) {
+        self.bindings.insert(code, button);
+    }
+
+    pub fn unbind(&mut self, code: u32) {
+        self.bindings.remove(&code);
+    }
+
+    pub fn set_callback(&mut self, callback: ButtonCallback) {
+        self.callback = Some(callback);
+    }
+
+    /// The imgui button bound to `code`, or `None` after passing the event
+    /// to the callback.
+    pub fn translate(&mut self, code: u32, down: bool) -> Option<MouseButton> {
+        if let Some(button) = self.bindings.get(&code) {
+            return Some(*button);
+        }
+        if let Some(callback) = &mut self.callback {
+            callback(code, down);
+        }
+        None
+    }
+}
@@ -0,0 +1,100 @@
+use imgui::ClipboardBackend;
+
+/// Text MIME types, most preferred first.
+pub const TEXT_MIME_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "TEXT",
+    "STRING",
+];
+
+/// Access to the compositor's clipboard selection.
+///
+/// Implemented by the host on top of its data-device connection, or by a fake
+/// in tests. Every method returns `None`/`false` when the clipboard protocol
+/// is unavailable, in which case [`WaywinClipboard`] falls back to an
+/// in-process clipboard.
+pub trait ClipboardProvider: 'static {
+    /// MIME types offered by the current selection.
+    fn mime_types(&mut self) -> Option<Vec<String>>;
+    /// Reads the current selection in the given MIME type.
+    fn read(&mut self, mime_type: &str) -> Option<Vec<u8>>;
+    /// Takes ownership of the selection, offering `data` under `mime_types`.
+    fn write(&mut self, mime_types: &[&str], data: Vec<u8>) -> bool;
+}
+
+/// An [`imgui::ClipboardBackend`] backed by a [`ClipboardProvider`].
+pub struct WaywinClipboard<P> {
+    provider: P,
+    fallback: Option<String>,
+}
+impl<P: ClipboardProvider> WaywinClipboard<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            fallback: None,
+        }
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+
+    fn read_system(&mut self) -> Option<String> {
+        let offered = self.provider.mime_types()?;
+        // read with the offer's own spelling, e.g. `text/plain;charset=UTF-8`,
+        // since the source may only answer to that exact string
+        let (mime_type, offer) = TEXT_MIME_TYPES.iter().find_map(|mime| {
+            let offer = offered
+                .iter()
+                .find(|offer| offer.eq_ignore_ascii_case(mime))?;
+            Some((mime, offer))
+        })?;
+        let data = self.provider.read(offer)?;
+        Some(normalize(decode(mime_type, data)))
+    }
+}
+impl<P: ClipboardProvider> ClipboardBackend for WaywinClipboard<P> {
+    fn get(&mut self) -> Option<String> {
+        self.read_system().or_else(|| self.fallback.clone())
+    }
+
+    fn set(&mut self, value: &str) {
+        let value = normalize(value.to_owned());
+        if self
+            .provider
+            .write(TEXT_MIME_TYPES, value.clone().into_bytes())
+        {
+            self.fallback = None;
+        } else {
+            self.fallback = Some(value);
+        }
+    }
+}
+
+fn decode(mime_type: &str, data: Vec<u8>) -> String {
+    match mime_type {
+        // the legacy X11 atoms are Latin-1
+        "TEXT" | "STRING" => data.into_iter().map(char::from).collect(),
+        _ => match String::from_utf8(data) {
+            Ok(text) => text,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        },
+    }
+}
+
+/// Converts line endings to `\n` and drops NULs, which imgui would treat as
+/// the end of the string.
+fn normalize(text: String) -> String {
+    if !text.contains(['\r', '\0']) {
+        return text;
+    }
+    text.replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\0', "")
+}
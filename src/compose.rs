@@ -0,0 +1,421 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Compose sequences, keyed by xkb keysym names.
+///
+/// Tables use the format of X11 `Compose` files, e.g.
+///
+/// ```text
+/// <dead_acute> <e> : "é" eacute
+/// <Multi_key> <o> <c> : "©" copyright
+/// ```
+///
+/// Sequences may only start with a dead key or the Compose key
+/// (`Multi_key`); modifier prefixes are ignored. `include` lines are followed
+/// when a table is loaded from a file. The default table covers the common
+/// dead keys over Latin letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposeTable {
+    sequences: HashMap<Vec<String>, String>,
+    prefixes: HashSet<Vec<String>>,
+}
+impl ComposeTable {
+    /// A table without any sequences.
+    pub fn empty() -> Self {
+        Self {
+            sequences: HashMap::new(),
+            prefixes: HashSet::new(),
+        }
+    }
+
+    /// Adds a sequence, replacing any sequence with the same keys.
+    pub fn insert<S: AsRef<str>>(&mut self, keys: &[S], result: impl Into<String>) {
+        let keys: Vec<String> = keys.iter().map(|key| key_name(key.as_ref())).collect();
+        if keys.is_empty() {
+            return;
+        }
+        for len in 1..keys.len() {
+            self.prefixes.insert(keys[..len].to_vec());
+        }
+        self.sequences.insert(keys, result.into());
+    }
+
+    /// Applies the sequences of `other` on top of this table.
+    pub fn merge(&mut self, other: ComposeTable) {
+        self.sequences.extend(other.sequences);
+        self.prefixes.extend(other.prefixes);
+    }
+
+    /// Parses a table in `Compose` file format, skipping lines it does not
+    /// understand. `include` lines are skipped as well.
+    pub fn parse(text: &str) -> Self {
+        let mut table = Self::empty();
+        for line in text.lines() {
+            if let Some((keys, result)) = parse_line(line) {
+                table.insert(&keys, result);
+            }
+        }
+        table
+    }
+
+    /// Loads a `Compose` file, following its `include` lines.
+    ///
+    /// Includes may use `%H` for the home directory, `%L` for the system
+    /// table of the current locale and `%S` for the system locale directory.
+    /// Like libX11, includes that cannot be read are skipped.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut table = Self::empty();
+        table.load_into(path.as_ref(), &XLocale::current(), 0)?;
+        Ok(table)
+    }
+
+    /// Loads the user's compose table, looked up like libX11 and xkbcommon
+    /// do: `$XCOMPOSEFILE`, then `~/.XCompose`, then the system table for
+    /// the locale in `$LC_ALL`, `$LC_CTYPE` or `$LANG`. Only the first table
+    /// found is loaded, so user tables usually `include "%L"`. The default
+    /// table is used as a base.
+    pub fn system() -> Self {
+        let mut table = Self::default();
+        let candidates = [
+            env::var_os("XCOMPOSEFILE").map(PathBuf::from),
+            env::var_os("HOME").map(|home| Path::new(&home).join(".XCompose")),
+            XLocale::current().compose_file(),
+        ];
+        if let Some(loaded) = candidates
+            .into_iter()
+            .flatten()
+            .find_map(|path| Self::load(path).ok())
+        {
+            table.merge(loaded);
+        }
+        table
+    }
+
+    fn load_into(&mut self, path: &Path, locale: &XLocale, depth: u32) -> io::Result<()> {
+        for line in fs::read_to_string(path)?.lines() {
+            if let Some(include) = parse_include(line) {
+                if depth < MAX_INCLUDE_DEPTH
+                    && let Some(include) = locale.expand(include)
+                {
+                    let _ = self.load_into(&include, locale, depth + 1);
+                }
+            } else if let Some((keys, result)) = parse_line(line) {
+                self.insert(&keys, result);
+            }
+        }
+        Ok(())
+    }
+
+    fn starts(&self, key: &str) -> bool {
+        let keys = [key.to_owned()];
+        self.prefixes.contains(keys.as_slice()) || self.sequences.contains_key(keys.as_slice())
+    }
+}
+impl Default for ComposeTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for &(dead, spacing, pairs) in DEAD_KEYS {
+            let spacing = spacing.to_string();
+            table.insert(&[dead, "space"], spacing.clone());
+            table.insert(&[dead, dead], spacing);
+            let mut chars = pairs.chars();
+            while let (Some(base), Some(composed)) = (chars.next(), chars.next()) {
+                table.insert(&[dead, base.to_string().as_str()], composed.to_string());
+            }
+        }
+        table
+    }
+}
+
+/// Guards against include cycles.
+const MAX_INCLUDE_DEPTH: u32 = 8;
+
+/// The locale `include` lines are resolved for, found the way libX11 and
+/// xkbcommon find it.
+struct XLocale {
+    /// `$XLOCALEDIR`, or the usual system directory.
+    dir: PathBuf,
+    name: String,
+}
+impl XLocale {
+    fn current() -> Self {
+        let name = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|var| env::var(var).ok().filter(|name| !name.is_empty()))
+            .unwrap_or_else(|| "C".to_owned());
+        let dir = env::var_os("XLOCALEDIR")
+            .map_or_else(|| PathBuf::from("/usr/share/X11/locale"), PathBuf::from);
+        Self { dir, name }
+    }
+
+    /// The system table for the locale, after resolving aliases such as
+    /// `en_US.utf8` through `locale.alias`.
+    fn compose_file(&self) -> Option<PathBuf> {
+        let aliases = read_locale_table(&self.dir.join("locale.alias"));
+        let name = aliases
+            .iter()
+            .find(|(alias, _)| *alias == self.name)
+            .map_or(self.name.as_str(), |(_, name)| name.as_str());
+        let files = read_locale_table(&self.dir.join("compose.dir"));
+        let (file, _) = files.iter().find(|(_, locale)| locale == name)?;
+        Some(self.dir.join(file))
+    }
+
+    /// Expands `%H`, `%L`, `%S` and `%%` in an include path.
+    fn expand(&self, path: &str) -> Option<PathBuf> {
+        let mut expanded = String::new();
+        let mut chars = path.chars();
+        while let Some(char) = chars.next() {
+            if char != '%' {
+                expanded.push(char);
+                continue;
+            }
+            match chars.next()? {
+                'H' => expanded.push_str(&env::var("HOME").ok()?),
+                'L' => expanded.push_str(self.compose_file()?.to_str()?),
+                'S' => expanded.push_str(self.dir.to_str()?),
+                '%' => expanded.push('%'),
+                _ => return None,
+            }
+        }
+        Some(PathBuf::from(expanded))
+    }
+}
+
+/// Reads the `first: second` lines of `locale.alias` or `compose.dir`; the
+/// colon is missing in older entries.
+fn read_locale_table(path: &Path) -> Vec<(String, String)> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let first = fields.next()?;
+            let first = first.strip_suffix(':').unwrap_or(first);
+            Some((first.to_owned(), fields.next()?.to_owned()))
+        })
+        .collect()
+}
+
+/// What a key did to the compose sequence in progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ComposeStep {
+    Pending,
+    Composed(String),
+    /// The key does not continue the sequence. Like xkbcommon, both are
+    /// dropped.
+    Cancelled,
+}
+
+pub(crate) struct ComposeState {
+    pub table: ComposeTable,
+    pub show_pending: bool,
+    pending: Vec<String>,
+}
+impl ComposeState {
+    pub fn new() -> Self {
+        Self {
+            table: ComposeTable::default(),
+            show_pending: true,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Feeds a dead key or the Compose key.
+    pub fn feed_key(&mut self, keysym: &str) -> ComposeStep {
+        let key = key_name(keysym);
+        if self.pending.is_empty() && !self.table.starts(&key) {
+            return ComposeStep::Cancelled;
+        }
+        self.feed(key)
+    }
+
+    /// Feeds typed text, returning what should reach imgui.
+    pub fn feed_text(&mut self, text: impl IntoIterator<Item = char>) -> Vec<char> {
+        let mut output = Vec::new();
+        for char in text {
+            if self.pending.is_empty() {
+                output.push(char);
+                continue;
+            }
+            if let ComposeStep::Composed(composed) = self.feed(char.to_string()) {
+                output.extend(composed.chars());
+            }
+        }
+        output
+    }
+
+    /// The pending sequence as it should be shown to the user, if at all.
+    pub fn preedit(&self) -> Option<String> {
+        if !self.show_pending || self.pending.is_empty() {
+            return None;
+        }
+        Some(self.pending.iter().map(|key| display(key)).collect())
+    }
+
+    fn feed(&mut self, key: String) -> ComposeStep {
+        self.pending.push(key);
+        if let Some(result) = self.table.sequences.get(&self.pending) {
+            let result = result.clone();
+            self.pending.clear();
+            ComposeStep::Composed(result)
+        } else if self.table.prefixes.contains(&self.pending) {
+            ComposeStep::Pending
+        } else {
+            self.pending.clear();
+            ComposeStep::Cancelled
+        }
+    }
+}
+
+/// How a pending key is drawn: dead keys as their spacing accent, the
+/// Compose key as `·`.
+fn display(key: &str) -> String {
+    if key == "Multi_key" {
+        return "·".to_owned();
+    }
+    DEAD_KEYS
+        .iter()
+        .find(|(dead, _, _)| *dead == key)
+        .map_or_else(|| key.to_owned(), |(_, spacing, _)| spacing.to_string())
+}
+
+/// Parses `include "path"`.
+fn parse_include(line: &str) -> Option<&str> {
+    let path = line.trim().strip_prefix("include")?.trim_start();
+    path.strip_prefix('"')?.split('"').next()
+}
+
+/// Parses `<key> <key> ... : "result" [keysym]`.
+fn parse_line(line: &str) -> Option<(Vec<&str>, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with("include") {
+        return None;
+    }
+    let (keys, result) = line.split_once(':')?;
+    let keys = keys
+        .split_whitespace()
+        .map(|key| key.strip_prefix('<')?.strip_suffix('>'))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut chars = result.trim_start().strip_prefix('"')?.chars();
+    let mut result = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => match chars.next()? {
+                'n' => result.push('\n'),
+                escaped => result.push(escaped),
+            },
+            char => result.push(char),
+        }
+    }
+    Some((keys, result))
+}
+
+/// Normalises a keysym name so that keysyms for characters match the
+/// characters typed, e.g. `apostrophe` and `U0027` both become `'`.
+fn key_name(keysym: &str) -> String {
+    let mut chars = keysym.chars();
+    if let (Some(_), None) = (chars.next(), chars.next()) {
+        return keysym.to_owned();
+    }
+    if let Some((_, char)) = NAMED_CHARACTERS.iter().find(|(name, _)| *name == keysym) {
+        return char.to_string();
+    }
+    if let Some(char) = keysym
+        .strip_prefix('U')
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+    {
+        return char.to_string();
+    }
+    keysym.to_owned()
+}
+
+const NAMED_CHARACTERS: &[(&str, char)] = &[
+    ("space", ' '),
+    ("exclam", '!'),
+    ("quotedbl", '"'),
+    ("numbersign", '#'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("apostrophe", '\''),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("asterisk", '*'),
+    ("plus", '+'),
+    ("comma", ','),
+    ("minus", '-'),
+    ("period", '.'),
+    ("slash", '/'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("less", '<'),
+    ("equal", '='),
+    ("greater", '>'),
+    ("question", '?'),
+    ("at", '@'),
+    ("bracketleft", '['),
+    ("backslash", '\\'),
+    ("bracketright", ']'),
+    ("asciicircum", '^'),
+    ("underscore", '_'),
+    ("grave", '`'),
+    ("braceleft", '{'),
+    ("bar", '|'),
+    ("braceright", '}'),
+    ("asciitilde", '~'),
+];
+
+/// Dead keys with their spacing accent, and pairs of base and composed
+/// letters.
+const DEAD_KEYS: &[(&str, char, &str)] = &[
+    ("dead_grave", '`', "aàeèiìnǹoòuùwẁyỳAÀEÈIÌNǸOÒUÙWẀYỲ"),
+    (
+        "dead_acute",
+        '´',
+        "aácćeégǵiíkḱlĺmḿnńoópṕrŕsśuúwẃyýzźAÁCĆEÉGǴIÍKḰLĹMḾNŃOÓPṔRŔSŚUÚWẂYÝZŹ",
+    ),
+    (
+        "dead_circumflex",
+        '^',
+        "aâcĉeêgĝhĥiîjĵoôsŝuûwŵyŷzẑAÂCĈEÊGĜHĤIÎJĴOÔSŜUÛWŴYŶZẐ",
+    ),
+    ("dead_tilde", '~', "aãeẽiĩnñoõuũvṽyỹAÃEẼIĨNÑOÕUŨVṼYỸ"),
+    ("dead_macron", '¯', "aāeēgḡiīoōuūyȳAĀEĒGḠIĪOŌUŪYȲ"),
+    ("dead_breve", '˘', "aăeĕgğiĭoŏuŭAĂEĔGĞIĬOŎUŬ"),
+    (
+        "dead_abovedot",
+        '˙',
+        "aȧbḃcċdḋeėfḟgġhḣmṁnṅoȯpṗrṙsṡtṫwẇxẋyẏzżAȦBḂCĊDḊEĖFḞGĠHḢIİMṀNṄOȮPṖRṘSṠTṪWẆXẊYẎZŻ",
+    ),
+    (
+        "dead_diaeresis",
+        '¨',
+        "aäeëhḧiïoötẗuüwẅxẍyÿAÄEËHḦIÏOÖUÜWẄXẌYŸ",
+    ),
+    ("dead_abovering", '˚', "aåuůwẘyẙAÅUŮ"),
+    ("dead_doubleacute", '˝', "oőuűOŐUŰ"),
+    (
+        "dead_caron",
+        'ˇ',
+        "aǎcčdďeěgǧhȟiǐjǰkǩlľnňoǒrřsštťuǔzžAǍCČDĎEĚGǦHȞIǏKǨLĽNŇOǑRŘSŠTŤUǓZŽ",
+    ),
+    (
+        "dead_cedilla",
+        '¸',
+        "cçdḑeȩgģhḩkķlļnņrŗsştţCÇDḐEȨGĢHḨKĶLĻNŅRŖSŞTŢ",
+    ),
+    ("dead_ogonek", '˛', "aąeęiįoǫuųAĄEĘIĮOǪUŲ"),
+];
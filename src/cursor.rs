@@ -0,0 +1,78 @@
+use imgui::MouseCursor;
+
+/// A pointer shape, named after the `cursor-shape-v1` / CSS cursor names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    Default,
+    Text,
+    Move,
+    NsResize,
+    EwResize,
+    NeswResize,
+    NwseResize,
+    Pointer,
+    NotAllowed,
+}
+impl From<MouseCursor> for CursorShape {
+    fn from(cursor: MouseCursor) -> Self {
+        match cursor {
+            MouseCursor::Arrow => Self::Default,
+            MouseCursor::TextInput => Self::Text,
+            MouseCursor::ResizeAll => Self::Move,
+            MouseCursor::ResizeNS => Self::NsResize,
+            MouseCursor::ResizeEW => Self::EwResize,
+            MouseCursor::ResizeNESW => Self::NeswResize,
+            MouseCursor::ResizeNWSE => Self::NwseResize,
+            MouseCursor::Hand => Self::Pointer,
+            MouseCursor::NotAllowed => Self::NotAllowed,
+        }
+    }
+}
+
+/// Called with the new cursor, or `None` when the OS cursor should be hidden.
+pub type CursorCallback = Box<dyn FnMut(Option<CursorShape>)>;
+
+/// Called with the position, in logical window coordinates, the pointer should
+/// be moved to.
+pub type WarpCallback = Box<dyn FnMut(f64, f64)>;
+
+pub(crate) struct CursorState {
+    callback: Option<CursorCallback>,
+    /// `None` until the first cursor has been pushed.
+    current: Option<Option<CursorShape>>,
+    warp: Option<WarpCallback>,
+}
+impl CursorState {
+    pub fn new() -> Self {
+        Self {
+            callback: None,
+            current: None,
+            warp: None,
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: CursorCallback) {
+        self.callback = Some(callback);
+        self.current = None;
+    }
+
+    pub fn set_warp_callback(&mut self, warp: WarpCallback) {
+        self.warp = Some(warp);
+    }
+
+    pub fn warp(&mut self, x: f64, y: f64) {
+        if let Some(warp) = &mut self.warp {
+            warp(x, y);
+        }
+    }
+
+    pub fn update(&mut self, cursor: Option<CursorShape>) {
+        if self.current == Some(cursor) {
+            return;
+        }
+        if let Some(callback) = &mut self.callback {
+            callback(cursor);
+            self.current = Some(cursor);
+        }
+    }
+}
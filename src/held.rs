@@ -0,0 +1,62 @@
+use crate::record::{Input, InputEvent};
+use imgui::{Io, Key, MouseButton, sys};
+
+/// Keys and buttons imgui currently believes are held, so they can be
+/// released when focus is lost without the matching release event.
+pub(crate) struct HeldInputs {
+    keys: Vec<Key>,
+    buttons: Vec<MouseButton>,
+}
+impl HeldInputs {
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            buttons: Vec::new(),
+        }
+    }
+
+    pub fn add_key_event(&mut self, input: &mut Input, key: Key, down: bool) {
+        track(&mut self.keys, key, down);
+        input.send(InputEvent::Key { key, down });
+    }
+
+    pub fn add_mouse_button_event(&mut self, input: &mut Input, button: MouseButton, down: bool) {
+        track(&mut self.buttons, button, down);
+        input.send(InputEvent::MouseButton { button, down });
+    }
+
+    /// Queues a release for everything still held.
+    pub fn release_all(&mut self, input: &mut Input) {
+        for key in self.keys.drain(..) {
+            input.send(InputEvent::Key { key, down: false });
+        }
+        self.release_buttons(input);
+    }
+
+    pub fn release_buttons(&mut self, input: &mut Input) {
+        for button in self.buttons.drain(..) {
+            input.send(InputEvent::MouseButton {
+                button,
+                down: false,
+            });
+        }
+    }
+}
+
+fn track<T: PartialEq>(held: &mut Vec<T>, input: T, down: bool) {
+    let index = held.iter().position(|held| *held == input);
+    match (index, down) {
+        (None, true) => held.push(input),
+        (Some(index), false) => {
+            held.swap_remove(index);
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn add_focus_event(io: &mut Io, focused: bool) {
+    // SAFETY: `Io` is a transparent wrapper around `ImGuiIO`
+    unsafe {
+        sys::ImGuiIO_AddFocusEvent(io as *mut Io as *mut sys::ImGuiIO, focused);
+    }
+}
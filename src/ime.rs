@@ -0,0 +1,208 @@
+use crate::{
+    Modifiers, TextFilter,
+    record::{Input, InputEvent},
+};
+use imgui::{Context, ImColor32, StyleColor, Ui, sys};
+use std::{cell::RefCell, collections::HashMap};
+
+/// Input method events, as delivered by the text-input protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImeEvent {
+    /// The text being composed, replacing any previous preedit.
+    ///
+    /// `cursor` is a byte range into `text`; an empty string ends the
+    /// composition without committing anything.
+    Preedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// Text to insert at the caret.
+    Commit(String),
+}
+
+/// Requests for the host's text-input protocol object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImeRequest {
+    /// A text field gained focus.
+    Enable,
+    /// No text field is focused anymore.
+    Disable,
+    /// The caret rectangle, in logical window coordinates, next to which the
+    /// candidate window should appear.
+    CursorArea {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+pub type ImeCallback = Box<dyn FnMut(ImeRequest)>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ImeData {
+    pos: [f32; 2],
+    line_height: f32,
+}
+
+thread_local! {
+    /// Latest IME data per imgui context, keyed by the context's address.
+    /// Entries are removed when the platform of their context is dropped.
+    static IME_DATA: RefCell<HashMap<usize, Option<ImeData>>> = RefCell::default();
+}
+
+fn current_context() -> usize {
+    // SAFETY: only reads the global context pointer
+    unsafe { sys::igGetCurrentContext() as usize }
+}
+
+unsafe extern "C" fn set_platform_ime_data(
+    _viewport: *mut sys::ImGuiViewport,
+    data: *mut sys::ImGuiPlatformImeData,
+) {
+    // SAFETY: imgui passes a valid pointer for the duration of the call
+    let data = unsafe { &*data };
+    let data = data.WantVisible.then_some(ImeData {
+        pos: [data.InputPos.x, data.InputPos.y],
+        line_height: data.InputLineHeight,
+    });
+    IME_DATA.with_borrow_mut(|ime_data| ime_data.insert(current_context(), data));
+}
+
+struct Preedit {
+    text: String,
+    cursor: Option<(usize, usize)>,
+}
+
+pub(crate) struct ImeState {
+    /// Key of this context's entry in `IME_DATA`.
+    context: usize,
+    callback: Option<ImeCallback>,
+    enabled: bool,
+    area: Option<ImeData>,
+    preedit: Option<Preedit>,
+    /// A pending dead key or compose sequence, shown when the input method
+    /// has no preedit of its own.
+    compose: Option<Preedit>,
+}
+impl ImeState {
+    pub fn new(imgui: &mut Context) -> Self {
+        imgui.io_mut().set_platform_ime_data_fn = Some(set_platform_ime_data);
+        // a context borrowed mutably is the current one
+        let context = current_context();
+        IME_DATA.with_borrow_mut(|ime_data| ime_data.remove(&context));
+        Self {
+            context,
+            callback: None,
+            enabled: false,
+            area: None,
+            preedit: None,
+            compose: None,
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: ImeCallback) {
+        self.callback = Some(callback);
+        self.enabled = false;
+        self.area = None;
+    }
+
+    pub fn handle_event(&mut self, input: &mut Input, filter: &TextFilter, event: ImeEvent) {
+        match event {
+            ImeEvent::Preedit { text, cursor } => {
+                self.preedit = (!text.is_empty()).then_some(Preedit { text, cursor });
+            }
+            ImeEvent::Commit(text) => {
+                self.preedit = None;
+                for char in filter.filter(&text, Modifiers::default()) {
+                    input.send(InputEvent::Character(char));
+                }
+            }
+        }
+    }
+
+    pub fn set_compose_preedit(&mut self, text: Option<String>) {
+        self.compose = text.map(|text| Preedit { text, cursor: None });
+    }
+
+    pub fn prepare_render(&mut self, ui: &Ui) {
+        let want_text_input = ui.io().want_text_input;
+        if !want_text_input {
+            self.preedit = None;
+        }
+
+        let Some(callback) = &mut self.callback else {
+            return;
+        };
+        if want_text_input != self.enabled {
+            self.enabled = want_text_input;
+            self.area = None;
+            callback(if want_text_input {
+                ImeRequest::Enable
+            } else {
+                ImeRequest::Disable
+            });
+        }
+
+        let area = IME_DATA.with_borrow(|ime_data| ime_data.get(&self.context).copied().flatten());
+        if self.enabled && area.is_some() && area != self.area {
+            self.area = area;
+            if let Some(area) = area {
+                callback(ImeRequest::CursorArea {
+                    x: area.pos[0],
+                    y: area.pos[1],
+                    width: 1.0,
+                    height: area.line_height,
+                });
+            }
+        }
+
+        self.draw_preedit(ui, area);
+    }
+
+    /// Draws the preedit text over the text field, underlined, with the
+    /// cursor range highlighted.
+    fn draw_preedit(&self, ui: &Ui, area: Option<ImeData>) {
+        let preedit = self.preedit.as_ref().or(self.compose.as_ref());
+        let (Some(preedit), Some(area)) = (preedit, area) else {
+            return;
+        };
+        let [x, y] = area.pos;
+        let [width, _] = ui.calc_text_size(&preedit.text);
+        let bottom = y + area.line_height;
+
+        let draw_list = ui.get_foreground_draw_list();
+        let background = ImColor32::from(ui.style_color(StyleColor::FrameBg));
+        let text = ImColor32::from(ui.style_color(StyleColor::Text));
+        draw_list
+            .add_rect([x, y], [x + width, bottom], background)
+            .filled(true)
+            .build();
+        if let Some((start, end)) = preedit.cursor {
+            let offset = |i: usize| match preedit.text.get(..i) {
+                Some(prefix) => ui.calc_text_size(prefix)[0],
+                None => width,
+            };
+            let (start, end) = (offset(start), offset(end));
+            let highlight = ImColor32::from(ui.style_color(StyleColor::TextSelectedBg));
+            draw_list
+                .add_rect(
+                    [x + start, y],
+                    [x + end.max(start + 1.0), bottom],
+                    highlight,
+                )
+                .filled(true)
+                .build();
+        }
+        draw_list.add_text([x, y], text, &preedit.text);
+        draw_list
+            .add_line([x, bottom - 1.0], [x + width, bottom - 1.0], text)
+            .build();
+    }
+}
+impl Drop for ImeState {
+    fn drop(&mut self) {
+        // the map is gone already if the thread is exiting
+        let _ = IME_DATA.try_with(|ime_data| ime_data.borrow_mut().remove(&self.context));
+    }
+}
@@ -0,0 +1,400 @@
+use crate::keys::{KEY_NAMES, key_name};
+use std::{collections::HashMap, error::Error, fmt, fs, io, path::Path, str::FromStr};
+use waywin::event::LogicalKey;
+
+/// A key as reported by waywin, before translation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyInput {
+    /// A named logical key, by its waywin name, e.g. `"Tab"` or `"NumpadEnter"`.
+    Named(String),
+    /// A logical key that produces a character, without modifiers applied.
+    Character(String),
+    /// A physical key by its Linux evdev scancode.
+    Scancode(u32),
+}
+
+/// The table translating key events into [`imgui::Key`]s.
+///
+/// Key maps are stored as text, one binding per line:
+///
+/// ```text
+/// # comment
+/// key NumpadEnter = Enter
+/// char a = A
+/// scancode 30 = A
+/// key Menu = none
+/// ```
+///
+/// Whitespace characters are written as `U+XXXX`. Binding to `none` removes
+/// a binding when [merged](Self::merge) into another map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: HashMap<KeyInput, Option<imgui::Key>>,
+}
+impl KeyMap {
+    /// A key map without any bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn bind(&mut self, input: KeyInput, key: imgui::Key) {
+        self.bindings.insert(input, Some(key));
+    }
+
+    /// Marks `input` as producing no key.
+    pub fn unbind(&mut self, input: KeyInput) {
+        self.bindings.insert(input, None);
+    }
+
+    pub fn get(&self, input: &KeyInput) -> Option<imgui::Key> {
+        self.bindings.get(input).copied().flatten()
+    }
+
+    /// Applies the bindings of `other` on top of this map.
+    pub fn merge(&mut self, other: KeyMap) {
+        self.bindings.extend(other.bindings);
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub(crate) fn logical(&self, key: LogicalKey<&str>) -> Option<imgui::Key> {
+        let input = match key {
+            LogicalKey::Key(key) => KeyInput::Named(key_name(&key)?.to_owned()),
+            LogicalKey::Character(text) => KeyInput::Character(text.to_owned()),
+            _ => return None,
+        };
+        self.get(&input)
+    }
+
+    pub(crate) fn physical(&self, code: u32) -> Option<imgui::Key> {
+        self.get(&KeyInput::Scancode(code))
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let named = DEFAULT_NAMED
+            .iter()
+            .map(|(name, key)| (KeyInput::Named(name.to_string()), Some(*key)));
+        let characters = DEFAULT_CHARACTERS
+            .iter()
+            .map(|(text, key)| (KeyInput::Character(text.to_string()), Some(*key)));
+        let scancodes = DEFAULT_SCANCODES
+            .iter()
+            .map(|(code, key)| (KeyInput::Scancode(*code), Some(*key)));
+        Self {
+            bindings: named.chain(characters).chain(scancodes).collect(),
+        }
+    }
+}
+
+impl fmt::Display for KeyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bindings: Vec<_> = self.bindings.iter().collect();
+        bindings.sort_by(|a, b| a.0.cmp(b.0));
+        for (input, key) in bindings {
+            match input {
+                KeyInput::Named(name) => write!(f, "key {name}")?,
+                KeyInput::Character(text) if text.contains(char::is_whitespace) => {
+                    write!(f, "char ")?;
+                    for c in text.chars() {
+                        write!(f, "U+{:04X}", c as u32)?;
+                    }
+                }
+                KeyInput::Character(text) => write!(f, "char {text}")?,
+                KeyInput::Scancode(code) => write!(f, "scancode {code}")?,
+            }
+            match key {
+                Some(key) => writeln!(f, " = {key:?}")?,
+                None => writeln!(f, " = none")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyMapError {
+    /// 1-based line number.
+    pub line: usize,
+    pub reason: &'static str,
+}
+impl fmt::Display for ParseKeyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+impl Error for ParseKeyMapError {}
+
+impl FromStr for KeyMap {
+    type Err = ParseKeyMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut map = Self::empty();
+        for (index, line) in s.lines().enumerate() {
+            let error = |reason| ParseKeyMapError {
+                line: index + 1,
+                reason,
+            };
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let (input, key) = line
+                .rsplit_once(" = ")
+                .ok_or_else(|| error("expected `=`"))?;
+            let (kind, value) = input
+                .trim_start()
+                .split_once(' ')
+                .ok_or_else(|| error("expected a key kind"))?;
+            let input = match kind {
+                "key" if KEY_NAMES.contains(&value) => KeyInput::Named(value.to_owned()),
+                "key" => return Err(error("unknown waywin key")),
+                "char" => KeyInput::Character(
+                    parse_character(value).ok_or_else(|| error("invalid character"))?,
+                ),
+                "scancode" => {
+                    KeyInput::Scancode(value.parse().map_err(|_| error("invalid scancode"))?)
+                }
+                _ => return Err(error("expected `key`, `char` or `scancode`")),
+            };
+            let key = match key.trim() {
+                "none" => None,
+                name => Some(
+                    imgui::Key::VARIANTS
+                        .into_iter()
+                        .find(|key| format!("{key:?}") == name)
+                        .ok_or_else(|| error("unknown imgui key"))?,
+                ),
+            };
+            map.bindings.insert(input, key);
+        }
+        Ok(map)
+    }
+}
+
+/// Parses a character value, decoding `U+XXXX` escapes.
+fn parse_character(value: &str) -> Option<String> {
+    if !value.starts_with("U+") {
+        return (!value.is_empty()).then(|| value.to_owned());
+    }
+    value
+        .split("U+")
+        .skip(1)
+        .map(|hex| char::from_u32(u32::from_str_radix(hex, 16).ok()?))
+        .collect()
+}
+
+/// Keypad keys are bound by scancode only, see [`KeyMappingMode`](crate::KeyMappingMode).
+const DEFAULT_NAMED: &[(&str, imgui::Key)] = &[
+    ("Tab", imgui::Key::Tab),
+    ("LeftArrow", imgui::Key::LeftArrow),
+    ("RightArrow", imgui::Key::RightArrow),
+    ("UpArrow", imgui::Key::UpArrow),
+    ("DownArrow", imgui::Key::DownArrow),
+    ("PageUp", imgui::Key::PageUp),
+    ("PageDown", imgui::Key::PageDown),
+    ("Home", imgui::Key::Home),
+    ("End", imgui::Key::End),
+    ("Insert", imgui::Key::Insert),
+    ("Delete", imgui::Key::Delete),
+    ("Backspace", imgui::Key::Backspace),
+    ("Space", imgui::Key::Space),
+    ("Enter", imgui::Key::Enter),
+    ("Escape", imgui::Key::Escape),
+    ("LCtrl", imgui::Key::LeftCtrl),
+    ("LShift", imgui::Key::LeftShift),
+    ("LAlt", imgui::Key::LeftAlt),
+    ("LSuper", imgui::Key::LeftSuper),
+    ("RCtrl", imgui::Key::RightCtrl),
+    ("RShift", imgui::Key::RightShift),
+    ("RAlt", imgui::Key::RightAlt),
+    ("RSuper", imgui::Key::RightSuper),
+    ("Menu", imgui::Key::Menu),
+    ("Key0", imgui::Key::Alpha0),
+    ("Key1", imgui::Key::Alpha1),
+    ("Key2", imgui::Key::Alpha2),
+    ("Key3", imgui::Key::Alpha3),
+    ("Key4", imgui::Key::Alpha4),
+    ("Key5", imgui::Key::Alpha5),
+    ("Key6", imgui::Key::Alpha6),
+    ("Key7", imgui::Key::Alpha7),
+    ("Key8", imgui::Key::Alpha8),
+    ("Key9", imgui::Key::Alpha9),
+    ("F1", imgui::Key::F1),
+    ("F2", imgui::Key::F2),
+    ("F3", imgui::Key::F3),
+    ("F4", imgui::Key::F4),
+    ("F5", imgui::Key::F5),
+    ("F6", imgui::Key::F6),
+    ("F7", imgui::Key::F7),
+    ("F8", imgui::Key::F8),
+    ("F9", imgui::Key::F9),
+    ("F10", imgui::Key::F10),
+    ("F11", imgui::Key::F11),
+    ("F12", imgui::Key::F12),
+    ("Minus", imgui::Key::Minus),
+    ("Period", imgui::Key::Period),
+    ("Slash", imgui::Key::Slash),
+    ("CapsLock", imgui::Key::CapsLock),
+    ("ScrollLock", imgui::Key::ScrollLock),
+    ("NumLock", imgui::Key::NumLock),
+    ("PrintScreen", imgui::Key::PrintScreen),
+    ("Pause", imgui::Key::Pause),
+];
+
+const DEFAULT_CHARACTERS: &[(&str, imgui::Key)] = &[
+    ("a", imgui::Key::A),
+    ("b", imgui::Key::B),
+    ("c", imgui::Key::C),
+    ("d", imgui::Key::D),
+    ("e", imgui::Key::E),
+    ("f", imgui::Key::F),
+    ("g", imgui::Key::G),
+    ("h", imgui::Key::H),
+    ("i", imgui::Key::I),
+    ("j", imgui::Key::J),
+    ("k", imgui::Key::K),
+    ("l", imgui::Key::L),
+    ("m", imgui::Key::M),
+    ("n", imgui::Key::N),
+    ("o", imgui::Key::O),
+    ("p", imgui::Key::P),
+    ("q", imgui::Key::Q),
+    ("r", imgui::Key::R),
+    ("s", imgui::Key::S),
+    ("t", imgui::Key::T),
+    ("u", imgui::Key::U),
+    ("v", imgui::Key::V),
+    ("w", imgui::Key::W),
+    ("x", imgui::Key::X),
+    ("y", imgui::Key::Y),
+    ("z", imgui::Key::Z),
+    ("'", imgui::Key::Apostrophe),
+    (",", imgui::Key::Comma),
+    (";", imgui::Key::Semicolon),
+    ("=", imgui::Key::Equal),
+    ("[", imgui::Key::LeftBracket),
+    ("\\", imgui::Key::Backslash),
+    ("]", imgui::Key::RightBracket),
+    ("`", imgui::Key::GraveAccent),
+];
+
+/// Linux evdev scancodes.
+const DEFAULT_SCANCODES: &[(u32, imgui::Key)] = &[
+    (1, imgui::Key::Escape),
+    (2, imgui::Key::Alpha1),
+    (3, imgui::Key::Alpha2),
+    (4, imgui::Key::Alpha3),
+    (5, imgui::Key::Alpha4),
+    (6, imgui::Key::Alpha5),
+    (7, imgui::Key::Alpha6),
+    (8, imgui::Key::Alpha7),
+    (9, imgui::Key::Alpha8),
+    (10, imgui::Key::Alpha9),
+    (11, imgui::Key::Alpha0),
+    (12, imgui::Key::Minus),
+    (13, imgui::Key::Equal),
+    (14, imgui::Key::Backspace),
+    (15, imgui::Key::Tab),
+    (16, imgui::Key::Q),
+    (17, imgui::Key::W),
+    (18, imgui::Key::E),
+    (19, imgui::Key::R),
+    (20, imgui::Key::T),
+    (21, imgui::Key::Y),
+    (22, imgui::Key::U),
+    (23, imgui::Key::I),
+    (24, imgui::Key::O),
+    (25, imgui::Key::P),
+    (26, imgui::Key::LeftBracket),
+    (27, imgui::Key::RightBracket),
+    (28, imgui::Key::Enter),
+    (29, imgui::Key::LeftCtrl),
+    (30, imgui::Key::A),
+    (31, imgui::Key::S),
+    (32, imgui::Key::D),
+    (33, imgui::Key::F),
+    (34, imgui::Key::G),
+    (35, imgui::Key::H),
+    (36, imgui::Key::J),
+    (37, imgui::Key::K),
+    (38, imgui::Key::L),
+    (39, imgui::Key::Semicolon),
+    (40, imgui::Key::Apostrophe),
+    (41, imgui::Key::GraveAccent),
+    (42, imgui::Key::LeftShift),
+    (43, imgui::Key::Backslash),
+    (44, imgui::Key::Z),
+    (45, imgui::Key::X),
+    (46, imgui::Key::C),
+    (47, imgui::Key::V),
+    (48, imgui::Key::B),
+    (49, imgui::Key::N),
+    (50, imgui::Key::M),
+    (51, imgui::Key::Comma),
+    (52, imgui::Key::Period),
+    (53, imgui::Key::Slash),
+    (54, imgui::Key::RightShift),
+    (55, imgui::Key::KeypadMultiply),
+    (56, imgui::Key::LeftAlt),
+    (57, imgui::Key::Space),
+    (58, imgui::Key::CapsLock),
+    (59, imgui::Key::F1),
+    (60, imgui::Key::F2),
+    (61, imgui::Key::F3),
+    (62, imgui::Key::F4),
+    (63, imgui::Key::F5),
+    (64, imgui::Key::F6),
+    (65, imgui::Key::F7),
+    (66, imgui::Key::F8),
+    (67, imgui::Key::F9),
+    (68, imgui::Key::F10),
+    (69, imgui::Key::NumLock),
+    (70, imgui::Key::ScrollLock),
+    (71, imgui::Key::Keypad7),
+    (72, imgui::Key::Keypad8),
+    (73, imgui::Key::Keypad9),
+    (74, imgui::Key::KeypadSubtract),
+    (75, imgui::Key::Keypad4),
+    (76, imgui::Key::Keypad5),
+    (77, imgui::Key::Keypad6),
+    (78, imgui::Key::KeypadAdd),
+    (79, imgui::Key::Keypad1),
+    (80, imgui::Key::Keypad2),
+    (81, imgui::Key::Keypad3),
+    (82, imgui::Key::Keypad0),
+    (83, imgui::Key::KeypadDecimal),
+    (87, imgui::Key::F11),
+    (88, imgui::Key::F12),
+    (96, imgui::Key::KeypadEnter),
+    (97, imgui::Key::RightCtrl),
+    (98, imgui::Key::KeypadDivide),
+    (99, imgui::Key::PrintScreen),
+    (100, imgui::Key::RightAlt),
+    (102, imgui::Key::Home),
+    (103, imgui::Key::UpArrow),
+    (104, imgui::Key::PageUp),
+    (105, imgui::Key::LeftArrow),
+    (106, imgui::Key::RightArrow),
+    (107, imgui::Key::End),
+    (108, imgui::Key::DownArrow),
+    (109, imgui::Key::PageDown),
+    (110, imgui::Key::Insert),
+    (111, imgui::Key::Delete),
+    (117, imgui::Key::KeypadEqual),
+    (119, imgui::Key::Pause),
+    (125, imgui::Key::LeftSuper),
+    (126, imgui::Key::RightSuper),
+    (127, imgui::Key::Menu),
+];
@@ -0,0 +1,144 @@
+use crate::KeyMap;
+use waywin::event::{Key, LogicalKey};
+
+/// How key events are translated into [`imgui::Key`]s.
+///
+/// Keypad keys are identified by their physical position, as their logical
+/// key depends on the Num Lock state. With Num Lock on, the keypad would
+/// otherwise report digits instead of `Keypad0..Keypad9`. Bindings for named
+/// keys still come first, so a binding like `key NumpadEnd = End` applies
+/// whenever the keypad reports that key, i.e. with Num Lock off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyMappingMode {
+    /// Use the key as labelled by the active keyboard layout.
+    #[default]
+    Logical,
+    /// Use the key's position on a US QWERTY keyboard, ignoring the layout.
+    Physical,
+    /// Use the physical position for letters, so shortcuts like WASD stay in
+    /// place on every layout, and the logical key for everything else.
+    Hybrid,
+}
+impl KeyMappingMode {
+    pub(crate) fn translate(
+        self,
+        key_map: &KeyMap,
+        physical_key: u32,
+        logical_key: LogicalKey<&str>,
+    ) -> Option<imgui::Key> {
+        let physical = key_map.physical(physical_key);
+        let named = matches!(logical_key, LogicalKey::Key(_));
+        let logical = key_map.logical(logical_key);
+        match self {
+            Self::Logical => logical
+                .filter(|_| named)
+                .or(physical.filter(is_keypad))
+                .or(logical),
+            Self::Physical => physical,
+            Self::Hybrid => logical
+                .filter(|_| named)
+                .or(physical.filter(|key| is_letter(key) || is_keypad(key)))
+                .or(logical),
+        }
+    }
+}
+
+fn is_keypad(key: &imgui::Key) -> bool {
+    (imgui::Key::Keypad0 as u32..=imgui::Key::KeypadEqual as u32).contains(&(*key as u32))
+}
+
+fn is_letter(key: &imgui::Key) -> bool {
+    (imgui::Key::A as u32..=imgui::Key::Z as u32).contains(&(*key as u32))
+}
+
+macro_rules! key_names {
+    ($($name:ident,)*) => {
+        /// The name of a waywin key, as used in key map files.
+        pub(crate) fn key_name(key: &Key) -> Option<&'static str> {
+            match key {
+                $(Key::$name => Some(stringify!($name)),)*
+                _ => None,
+            }
+        }
+
+        /// Every name [`key_name`] can return.
+        pub(crate) const KEY_NAMES: &[&str] = &[$(stringify!($name),)*];
+    };
+}
+key_names! {
+    Tab,
+    LeftArrow,
+    RightArrow,
+    UpArrow,
+    DownArrow,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Insert,
+    Delete,
+    Backspace,
+    Space,
+    Enter,
+    Escape,
+    LCtrl,
+    LShift,
+    LAlt,
+    LSuper,
+    RCtrl,
+    RShift,
+    RAlt,
+    RSuper,
+    Menu,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Minus,
+    Period,
+    NumpadDelete,
+    Slash,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    NumpadInsert,
+    NumpadEnd,
+    NumpadDownArrow,
+    NumpadPageDown,
+    NumpadLeftArrow,
+    NumpadBegin,
+    NumpadRightArrow,
+    NumpadHome,
+    NumpadUpArrow,
+    NumpadPageUp,
+    NumpadDecimal,
+    NumpadDivide,
+    NumpadMultiply,
+    NumpadSubtract,
+    NumpadAdd,
+    NumpadEnter,
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
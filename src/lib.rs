@@ -1,224 +1,657 @@
-use imgui::{Context, Ui};
+use buttons::ExtraButtons;
+use compose::{ComposeState, ComposeStep};
+use cursor::CursorState;
+use held::HeldInputs;
+use ime::ImeState;
+use imgui::{BackendFlags, Context, Io, Ui};
+use modifiers::ModifierTracker;
+use pen::PenState;
+use pointer::PointerState;
+use record::{Input, Recorder};
+use scale::ScaleState;
+use scroll::ScrollState;
+#[cfg(feature = "docking")]
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{
+    io::{self, Write},
+    path::Path,
+    time::Duration,
+};
+use time::FrameClock;
+use touch::TouchState;
+#[cfg(feature = "docking")]
+use viewports::Viewports;
 use waywin::{
     Window,
-    event::{Key, LogicalKey, PointerButton, ScrollDirection, WaywinEvent, WindowEvent},
+    event::{PointerButton, ScrollDirection, WaywinEvent, WindowEvent},
 };
 
-pub struct WaywinPlatform {}
+mod buttons;
+mod clipboard;
+mod compose;
+mod cursor;
+mod held;
+mod ime;
+mod keymap;
+mod keys;
+mod modifiers;
+mod multi;
+mod pen;
+mod pointer;
+mod record;
+mod response;
+mod scale;
+mod scroll;
+#[cfg(feature = "software")]
+mod shm;
+#[cfg(feature = "software")]
+mod software;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text;
+mod time;
+mod touch;
+#[cfg(feature = "docking")]
+mod viewports;
+mod window;
+
+pub use buttons::ButtonCallback;
+pub use clipboard::{ClipboardProvider, TEXT_MIME_TYPES, WaywinClipboard};
+pub use compose::ComposeTable;
+pub use cursor::{CursorCallback, CursorShape, WarpCallback};
+pub use ime::{ImeCallback, ImeEvent, ImeRequest};
+pub use keymap::{KeyInput, KeyMap, ParseKeyMapError};
+pub use keys::KeyMappingMode;
+pub use modifiers::{ModifierState, Modifiers};
+pub use multi::WaywinMultiPlatform;
+pub use pen::{PenEvent, PenSample, PenTool};
+pub use pointer::PointerEvent;
+pub use record::{InputEvent, ParseRecordingError, RecordedFrame, Recording, Replay};
+pub use response::{EventCategory, EventResponse};
+pub use scale::{FontBuilder, ScalePolicy};
+pub use scroll::{ScrollAxis, ScrollEvent, ScrollSettings, ScrollSource};
+#[cfg(feature = "software")]
+pub use shm::{SHM_FORMAT_ARGB8888, ShmBuffer, ShmHost, ShmPresenter};
+#[cfg(feature = "software")]
+pub use software::{Canvas, SoftwareRenderer, SoftwareTexture};
+pub use text::TextFilter;
+pub use time::{SystemClock, TimeSource};
+pub use touch::{TouchEvent, TouchSettings};
+#[cfg(feature = "docking")]
+pub use viewports::{Placement, ViewportHost};
+pub use window::{HeadlessWindow, PlatformWindow};
+
+pub struct WaywinPlatform {
+    clock: FrameClock,
+    compose: ComposeState,
+    cursor: CursorState,
+    extra_buttons: ExtraButtons,
+    held: HeldInputs,
+    ime: ImeState,
+    key_map: KeyMap,
+    key_mapping: KeyMappingMode,
+    modifiers: ModifierTracker,
+    pen: PenState,
+    pointer: PointerState,
+    recorder: Option<Recorder>,
+    scale: ScaleState,
+    scroll: ScrollState,
+    text_filter: TextFilter,
+    touch: TouchState,
+    #[cfg(feature = "docking")]
+    viewports: Option<Rc<RefCell<dyn Viewports>>>,
+}
 impl WaywinPlatform {
-    pub fn new(imgui: &mut Context, window: &Window) -> Self {
+    pub fn new<W: PlatformWindow + ?Sized>(imgui: &mut Context, window: &W) -> Self {
         let io = imgui.io_mut();
-        // io.backend_flags.insert(BackendFlags::HAS_MOUSE_CURSORS);
-        // io.backend_flags.insert(BackendFlags::HAS_SET_MOUSE_POS);
-        let size = window.get_physical_size();
-        io.display_size = [size.0 as f32, size.1 as f32];
+        scale::update_display(io, window);
         imgui.set_platform_name(format!("waywin {}", env!("CARGO_PKG_VERSION")));
 
-        Self {}
+        Self {
+            clock: FrameClock::new(),
+            compose: ComposeState::new(),
+            cursor: CursorState::new(),
+            extra_buttons: ExtraButtons::new(),
+            held: HeldInputs::new(),
+            ime: ImeState::new(imgui),
+            key_map: KeyMap::default(),
+            key_mapping: KeyMappingMode::default(),
+            modifiers: ModifierTracker::new(),
+            pen: PenState::new(),
+            pointer: PointerState::new(),
+            recorder: None,
+            scale: ScaleState::new(window),
+            scroll: ScrollState::new(),
+            text_filter: TextFilter::default(),
+            touch: TouchState::new(),
+            #[cfg(feature = "docking")]
+            viewports: None,
+        }
+    }
+
+    /// Installs a clipboard backend that reads and writes the system clipboard
+    /// through `provider`.
+    ///
+    /// Without it, copy and paste only work inside imgui.
+    pub fn enable_clipboard<P: ClipboardProvider>(&mut self, imgui: &mut Context, provider: P) {
+        imgui.set_clipboard_backend(WaywinClipboard::new(provider));
+    }
+
+    /// Sets the function that applies imgui's requested cursor to the window.
+    ///
+    /// It is only called when the cursor changes. `None` means the OS cursor
+    /// should be hidden, either because imgui draws its own or because no
+    /// cursor was requested. imgui only changes cursors once a callback is
+    /// installed.
+    pub fn set_cursor_callback<F: FnMut(Option<CursorShape>) + 'static>(
+        &mut self,
+        imgui: &mut Context,
+        callback: F,
+    ) {
+        self.cursor.set_callback(Box::new(callback));
+        imgui
+            .io_mut()
+            .backend_flags
+            .insert(BackendFlags::HAS_MOUSE_CURSORS);
+    }
+
+    /// Sets the function that moves the pointer when imgui asks for it, e.g.
+    /// during keyboard or gamepad navigation.
+    ///
+    /// The position is in logical window coordinates, the same space as
+    /// [`WindowEvent::PointerMoved`]. imgui only asks for warps once a
+    /// callback is installed.
+    pub fn set_warp_callback<F: FnMut(f64, f64) + 'static>(
+        &mut self,
+        imgui: &mut Context,
+        callback: F,
+    ) {
+        self.cursor.set_warp_callback(Box::new(callback));
+        imgui
+            .io_mut()
+            .backend_flags
+            .insert(BackendFlags::HAS_SET_MOUSE_POS);
+    }
+
+    /// Sets the function that drives the host's text-input protocol.
+    ///
+    /// It enables the input method while an imgui text field is focused and
+    /// reports the caret rectangle so the candidate window can follow it.
+    pub fn set_ime_callback<F: FnMut(ImeRequest) + 'static>(&mut self, callback: F) {
+        self.ime.set_callback(Box::new(callback));
+    }
+
+    /// Feeds an input method event into imgui.
+    ///
+    /// Committed text is queued as characters; preedit text is drawn over
+    /// the focused text field until it is committed or cleared.
+    pub fn handle_ime(&mut self, imgui: &mut Context, event: ImeEvent) {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.ime.handle_event(&mut input, &self.text_filter, event);
+    }
+
+    /// Feeds the compositor's modifier state into imgui.
+    ///
+    /// Once called, imgui's modifiers follow this state only, instead of
+    /// being inferred from modifier key events. Hosts should forward every
+    /// `wl_keyboard.modifiers` event, which also reports modifiers that were
+    /// pressed before the window got keyboard focus.
+    pub fn handle_modifiers(&mut self, imgui: &mut Context, state: ModifierState) {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.modifiers.set_state(&mut input, &mut self.held, state);
+    }
+
+    /// The modifiers in effect, including Caps Lock and Num Lock.
+    ///
+    /// Lock state is only known once the host calls
+    /// [`handle_modifiers`](Self::handle_modifiers).
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers.current()
+    }
+
+    /// Sets which typed text is passed on to imgui.
+    pub fn set_text_filter(&mut self, filter: TextFilter) {
+        self.text_filter = filter;
+    }
+
+    /// Feeds a dead key or the Compose key, by its xkb keysym name such as
+    /// `dead_acute` or `Multi_key`.
+    ///
+    /// The characters typed next complete the sequence and the composed
+    /// text is passed to imgui once. Hosts only need this if waywin does not
+    /// already compose text itself.
+    pub fn handle_compose_key(&mut self, imgui: &mut Context, keysym: &str) -> EventResponse {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        let response = EventResponse::keyboard(input.io, true);
+        if let ComposeStep::Composed(text) = self.compose.feed_key(keysym) {
+            for char in self.text_filter.filter(&text, Modifiers::default()) {
+                input.send(InputEvent::Character(char));
+            }
+        }
+        self.ime.set_compose_preedit(self.compose.preedit());
+        response
+    }
+
+    /// Replaces the table used by [`handle_compose_key`](Self::handle_compose_key),
+    /// e.g. with [`ComposeTable::system`].
+    pub fn set_compose_table(&mut self, table: ComposeTable) {
+        self.compose.reset();
+        self.compose.table = table;
+    }
+
+    /// Sets whether a pending dead key or compose sequence is drawn over
+    /// the focused text field, like input method preedit text.
+    pub fn set_show_compose_preedit(&mut self, show: bool) {
+        self.compose.show_pending = show;
+    }
+
+    /// Sets how key events are translated into [`imgui::Key`]s.
+    pub fn set_key_mapping_mode(&mut self, mode: KeyMappingMode) {
+        self.key_mapping = mode;
+    }
+
+    /// The table used to translate key events, for adding or overriding
+    /// bindings at runtime.
+    pub fn key_map_mut(&mut self) -> &mut KeyMap {
+        &mut self.key_map
     }
 
-    pub fn handle_event(&mut self, imgui: &mut Context, window: &Window, event: WaywinEvent) {
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.key_map = key_map;
+    }
+
+    /// Sets what is adjusted when the window's scale factor changes, and
+    /// applies it for the current scale.
+    pub fn set_scale_policy(&mut self, imgui: &mut Context, policy: ScalePolicy) {
+        self.scale.set_policy(imgui, policy);
+    }
+
+    /// The window's current scale factor.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale.scale()
+    }
+
+    /// Returns `true` once after [`ScalePolicy::rebuild_fonts`] rebuilt the
+    /// font atlas, meaning the renderer's font texture must be recreated.
+    pub fn fonts_rebuilt(&mut self) -> bool {
+        self.scale.take_fonts_rebuilt()
+    }
+
+    /// Binds a pointer button without a waywin name to an imgui button.
+    ///
+    /// `code` is the Linux button code; `BTN_FORWARD` (`0x115`) and up are
+    /// reported this way.
+    pub fn bind_mouse_button(&mut self, code: u32, button: imgui::MouseButton) {
+        self.extra_buttons.bind(code, button);
+    }
+
+    pub fn unbind_mouse_button(&mut self, code: u32) {
+        self.extra_buttons.unbind(code);
+    }
+
+    /// Sets the function that receives presses and releases of pointer
+    /// buttons that are not bound with [`bind_mouse_button`](Self::bind_mouse_button),
+    /// so the application can map them to its own actions.
+    pub fn set_extra_button_callback<F: FnMut(u32, bool) + 'static>(&mut self, callback: F) {
+        self.extra_buttons.set_callback(Box::new(callback));
+    }
+
+    /// Feeds the pointer entering or leaving the main window into imgui.
+    ///
+    /// While the pointer is away, imgui sees no mouse position, so hover
+    /// highlights and tooltips go away, and buttons held when it left are
+    /// released.
+    pub fn handle_pointer(&mut self, imgui: &mut Context, event: PointerEvent) -> EventResponse {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.pointer.handle_event(&mut input, &mut self.held, event)
+    }
+
+    pub fn set_scroll_settings(&mut self, settings: ScrollSettings) {
+        self.scroll.settings = settings;
+    }
+
+    /// Feeds a detailed scroll event of the main window into imgui.
+    ///
+    /// Prefer this over waywin's scroll events when the host has access to
+    /// the wheel's `value120` or the axis source.
+    pub fn handle_scroll(&mut self, imgui: &mut Context, event: ScrollEvent) -> EventResponse {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.scroll.handle_event(&mut input, event)
+    }
+
+    pub fn set_touch_settings(&mut self, settings: TouchSettings) {
+        self.touch.settings = settings;
+    }
+
+    /// Feeds a touch event of the main window into imgui as mouse input.
+    pub fn handle_touch(&mut self, imgui: &mut Context, event: TouchEvent) -> EventResponse {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.touch.handle_event(&mut input, &mut self.held, event)
+    }
+
+    /// Feeds a tablet tool event of the main window into imgui as mouse input.
+    ///
+    /// The tip acts as the left button, barrel buttons as right and middle.
+    pub fn handle_pen(&mut self, imgui: &mut Context, event: PenEvent) -> EventResponse {
+        let mut input = Input::new(imgui.io_mut(), self.recorder.as_mut(), &mut self.clock);
+        self.pen.handle_event(&mut input, &mut self.held, event)
+    }
+
+    /// The stylus state, or `None` while no tool is in proximity.
+    pub fn pen(&self) -> Option<PenSample> {
+        self.pen.current()
+    }
+
+    /// Stylus samples received since the last [`prepare_frame`](Self::prepare_frame),
+    /// oldest first.
+    pub fn pen_samples(&self) -> &[PenSample] {
+        self.pen.samples()
+    }
+
+    /// Lets imgui move windows out of `window` into their own platform
+    /// windows, managed by `host`.
+    #[cfg(feature = "docking")]
+    pub fn enable_viewports<W: PlatformWindow + ?Sized, H: ViewportHost>(
+        &mut self,
+        imgui: &mut Context,
+        window: &W,
+        host: H,
+    ) {
         let io = imgui.io_mut();
+        io.config_flags.insert(imgui::ConfigFlags::VIEWPORTS_ENABLE);
+        io.backend_flags
+            .insert(BackendFlags::PLATFORM_HAS_VIEWPORTS);
+
+        let viewports = viewports::install(imgui, host);
+        viewports
+            .borrow_mut()
+            .update_monitors(imgui, window.logical_size(), window.scale());
+        self.viewports = Some(viewports);
+    }
+
+    /// The window created for a viewport, for rendering its draw data.
+    ///
+    /// `W` is the host's [`ViewportHost::Window`], e.g. `viewport_window::<Window>(id)`.
+    #[cfg(feature = "docking")]
+    pub fn viewport_window<W: 'static>(&self, id: imgui::Id) -> Option<Arc<W>> {
+        let viewports = self.viewports.as_ref()?.borrow();
+        viewports.window(id)?.downcast_ref::<Arc<W>>().cloned()
+    }
+
+    /// Where a viewport window should be shown as a popup or subsurface of
+    /// the main window, or `None` if the host moved it itself.
+    #[cfg(feature = "docking")]
+    pub fn viewport_placement(&self, id: imgui::Id) -> Option<Placement> {
+        self.viewports.as_ref()?.borrow().placement(id)
+    }
+
+    /// Replaces the clock used to compute `io.delta_time`.
+    pub fn set_time_source<T: TimeSource>(&mut self, source: T) {
+        self.clock.set_source(Box::new(source));
+    }
+
+    /// Sets the longest frame time reported to imgui.
+    ///
+    /// Longer gaps, e.g. after a suspend, are clamped to this value.
+    pub fn set_max_frame_delta(&mut self, max_delta: Duration) {
+        self.clock.set_max_delta(max_delta);
+    }
+
+    /// Starts recording every event fed into imgui, along with the timing
+    /// of each frame, for later [replay](Replay).
+    ///
+    /// A running recording is stopped first; its result is returned.
+    pub fn start_recording<W: Write + 'static>(&mut self, writer: W) -> io::Result<()> {
+        let previous = self.stop_recording();
+        self.recorder = Some(Recorder::new(Box::new(writer), self.clock.now()));
+        previous
+    }
+
+    /// Starts recording to a new file at `path`.
+    pub fn record_to_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = record::create_file(path.as_ref())?;
+        let previous = self.stop_recording();
+        self.recorder = Some(Recorder::new(writer, self.clock.now()));
+        previous
+    }
+
+    /// Stops recording and flushes the output.
+    ///
+    /// Write errors do not interrupt the application; the first one is
+    /// returned here.
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Feeds a waywin event into imgui and reports whether imgui wants it.
+    pub fn handle_event(
+        &mut self,
+        imgui: &mut Context,
+        window: &Window,
+        event: WaywinEvent,
+    ) -> EventResponse {
         match event {
             WaywinEvent::WindowEvent { event, window_id } if window_id == window.id() => {
-                match event {
-                    WindowEvent::Resized => {
-                        let (w, h) = window.get_logical_size();
-                        io.display_size = [w as f32, h as f32];
-                    }
-                    WindowEvent::NewScaleFactor => {
-                        let scale = window.get_scale() as f32;
-                        io.display_framebuffer_scale = [scale, scale];
-                    }
-                    WindowEvent::Focus(focus) => {
-                        if !focus {
-                            io.app_focus_lost = true;
-                        }
-                    }
-                    WindowEvent::Key {
-                        down,
-                        physical_key: _,
-                        logical_key: _,
-                        text,
-                        text_raw: _,
-                        logical_key_unmodified,
-                    } => {
-                        for char in text.chars() {
-                            io.add_input_character(char);
-                        }
+                self.handle_window_event(imgui, window, event)
+            }
+            #[cfg(feature = "docking")]
+            WaywinEvent::WindowEvent { event, window_id } => {
+                let viewport = self.viewports.as_ref().and_then(|viewports| {
+                    viewports.borrow().find(&|window| {
+                        window
+                            .downcast_ref::<Window>()
+                            .is_some_and(|window| window.id() == window_id)
+                    })
+                });
+                match viewport {
+                    Some(viewport) => self.handle_viewport_event(imgui, viewport, event),
+                    None => EventResponse::Ignored,
+                }
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
 
-                        match &logical_key_unmodified {
-                            LogicalKey::Key(Key::LCtrl)
-                            | LogicalKey::Key(Key::RCtrl)
-                            | LogicalKey::Key(Key::Ctrl) => {
-                                io.add_key_event(imgui::Key::ModCtrl, down)
-                            }
-
-                            LogicalKey::Key(Key::LShift)
-                            | LogicalKey::Key(Key::RShift)
-                            | LogicalKey::Key(Key::Shift) => {
-                                io.add_key_event(imgui::Key::ModShift, down)
-                            }
-
-                            LogicalKey::Key(Key::LAlt)
-                            | LogicalKey::Key(Key::RAlt)
-                            | LogicalKey::Key(Key::Alt) => {
-                                io.add_key_event(imgui::Key::ModAlt, down)
-                            }
-
-                            LogicalKey::Key(Key::LSuper)
-                            | LogicalKey::Key(Key::RSuper)
-                            | LogicalKey::Key(Key::Super) => {
-                                io.add_key_event(imgui::Key::ModSuper, down)
-                            }
-
-                            _ => {}
-                        }
+    /// Feeds an event of the main window into imgui.
+    ///
+    /// [`handle_event`](Self::handle_event) calls this for events whose
+    /// window id matches; it is exposed so that a [`PlatformWindow`] other
+    /// than a waywin window, e.g. a [`HeadlessWindow`] in tests, can drive
+    /// the platform.
+    pub fn handle_window_event<W: PlatformWindow + ?Sized>(
+        &mut self,
+        imgui: &mut Context,
+        window: &W,
+        event: WindowEvent,
+    ) -> EventResponse {
+        let io = imgui.io_mut();
+        match event {
+            WindowEvent::Resized => {
+                scale::update_display(io, window);
+                EventResponse::Ignored
+            }
+            WindowEvent::NewScaleFactor => {
+                scale::update_display(io, window);
+                self.scale.set_scale(imgui, window.scale());
+                EventResponse::Ignored
+            }
+            WindowEvent::Focus(focus) => {
+                #[cfg(feature = "docking")]
+                if let Some(viewports) = &self.viewports {
+                    viewports.borrow_mut().set_main_focus(focus);
+                }
+                self.focus_changed(io, focus)
+            }
+            event => self.handle_input(io, event, [0.0, 0.0]),
+        }
+    }
 
-                        let key = match logical_key_unmodified.as_ref() {
-                            LogicalKey::Key(Key::Tab) => imgui::Key::Tab,
-                            LogicalKey::Key(Key::LeftArrow) => imgui::Key::LeftArrow,
-                            LogicalKey::Key(Key::RightArrow) => imgui::Key::RightArrow,
-                            LogicalKey::Key(Key::UpArrow) => imgui::Key::UpArrow,
-                            LogicalKey::Key(Key::DownArrow) => imgui::Key::DownArrow,
-                            LogicalKey::Key(Key::PageUp) => imgui::Key::PageUp,
-                            LogicalKey::Key(Key::PageDown) => imgui::Key::PageDown,
-                            LogicalKey::Key(Key::Home) => imgui::Key::Home,
-                            LogicalKey::Key(Key::End) => imgui::Key::End,
-                            LogicalKey::Key(Key::Insert) => imgui::Key::Insert,
-                            LogicalKey::Key(Key::Delete) => imgui::Key::Delete,
-                            LogicalKey::Key(Key::Backspace) => imgui::Key::Backspace,
-                            LogicalKey::Key(Key::Space) => imgui::Key::Space,
-                            LogicalKey::Key(Key::Enter) => imgui::Key::Enter,
-                            LogicalKey::Key(Key::Escape) => imgui::Key::Escape,
-                            LogicalKey::Key(Key::LCtrl) => imgui::Key::LeftCtrl,
-                            LogicalKey::Key(Key::LShift) => imgui::Key::LeftShift,
-                            LogicalKey::Key(Key::LAlt) => imgui::Key::LeftAlt,
-                            LogicalKey::Key(Key::LSuper) => imgui::Key::LeftSuper,
-                            LogicalKey::Key(Key::RCtrl) => imgui::Key::RightCtrl,
-                            LogicalKey::Key(Key::RShift) => imgui::Key::RightShift,
-                            LogicalKey::Key(Key::RAlt) => imgui::Key::RightAlt,
-                            LogicalKey::Key(Key::RSuper) => imgui::Key::RightSuper,
-                            LogicalKey::Key(Key::Menu) => imgui::Key::Menu,
-                            LogicalKey::Key(Key::Key0) => imgui::Key::Alpha0,
-                            LogicalKey::Key(Key::Key1) => imgui::Key::Alpha1,
-                            LogicalKey::Key(Key::Key2) => imgui::Key::Alpha2,
-                            LogicalKey::Key(Key::Key3) => imgui::Key::Alpha3,
-                            LogicalKey::Key(Key::Key4) => imgui::Key::Alpha4,
-                            LogicalKey::Key(Key::Key5) => imgui::Key::Alpha5,
-                            LogicalKey::Key(Key::Key6) => imgui::Key::Alpha6,
-                            LogicalKey::Key(Key::Key7) => imgui::Key::Alpha7,
-                            LogicalKey::Key(Key::Key8) => imgui::Key::Alpha8,
-                            LogicalKey::Key(Key::Key9) => imgui::Key::Alpha9,
-                            LogicalKey::Character("a") => imgui::Key::A,
-                            LogicalKey::Character("b") => imgui::Key::B,
-                            LogicalKey::Character("c") => imgui::Key::C,
-                            LogicalKey::Character("d") => imgui::Key::D,
-                            LogicalKey::Character("e") => imgui::Key::E,
-                            LogicalKey::Character("f") => imgui::Key::F,
-                            LogicalKey::Character("g") => imgui::Key::G,
-                            LogicalKey::Character("h") => imgui::Key::H,
-                            LogicalKey::Character("i") => imgui::Key::I,
-                            LogicalKey::Character("j") => imgui::Key::J,
-                            LogicalKey::Character("k") => imgui::Key::K,
-                            LogicalKey::Character("l") => imgui::Key::L,
-                            LogicalKey::Character("m") => imgui::Key::M,
-                            LogicalKey::Character("n") => imgui::Key::N,
-                            LogicalKey::Character("o") => imgui::Key::O,
-                            LogicalKey::Character("p") => imgui::Key::P,
-                            LogicalKey::Character("q") => imgui::Key::Q,
-                            LogicalKey::Character("r") => imgui::Key::R,
-                            LogicalKey::Character("s") => imgui::Key::S,
-                            LogicalKey::Character("t") => imgui::Key::T,
-                            LogicalKey::Character("u") => imgui::Key::U,
-                            LogicalKey::Character("v") => imgui::Key::V,
-                            LogicalKey::Character("w") => imgui::Key::W,
-                            LogicalKey::Character("x") => imgui::Key::X,
-                            LogicalKey::Character("y") => imgui::Key::Y,
-                            LogicalKey::Character("z") => imgui::Key::Z,
-                            LogicalKey::Key(Key::F1) => imgui::Key::F1,
-                            LogicalKey::Key(Key::F2) => imgui::Key::F2,
-                            LogicalKey::Key(Key::F3) => imgui::Key::F3,
-                            LogicalKey::Key(Key::F4) => imgui::Key::F4,
-                            LogicalKey::Key(Key::F5) => imgui::Key::F5,
-                            LogicalKey::Key(Key::F6) => imgui::Key::F6,
-                            LogicalKey::Key(Key::F7) => imgui::Key::F7,
-                            LogicalKey::Key(Key::F8) => imgui::Key::F8,
-                            LogicalKey::Key(Key::F9) => imgui::Key::F9,
-                            LogicalKey::Key(Key::F10) => imgui::Key::F10,
-                            LogicalKey::Key(Key::F11) => imgui::Key::F11,
-                            LogicalKey::Key(Key::F12) => imgui::Key::F12,
-                            LogicalKey::Character("'") => imgui::Key::Apostrophe,
-                            LogicalKey::Character(",") => imgui::Key::Comma,
-                            LogicalKey::Key(Key::Minus) => imgui::Key::Minus,
-                            LogicalKey::Key(Key::Period) | LogicalKey::Key(Key::NumpadDelete) => {
-                                imgui::Key::Period
-                            }
-                            LogicalKey::Key(Key::Slash) => imgui::Key::Slash,
-                            LogicalKey::Character(";") => imgui::Key::Semicolon,
-                            LogicalKey::Character("=") => imgui::Key::Equal,
-                            LogicalKey::Character("[") => imgui::Key::LeftBracket,
-                            LogicalKey::Character("\\") => imgui::Key::Backslash,
-                            LogicalKey::Character("]") => imgui::Key::RightBracket,
-                            // LogicalKey::Key(Key::Tab) => imgui::Key::GraveAccent,
-                            LogicalKey::Key(Key::CapsLock) => imgui::Key::CapsLock,
-                            LogicalKey::Key(Key::ScrollLock) => imgui::Key::ScrollLock,
-                            LogicalKey::Key(Key::NumLock) => imgui::Key::NumLock,
-                            LogicalKey::Key(Key::PrintScreen) => imgui::Key::PrintScreen,
-                            LogicalKey::Key(Key::Pause) => imgui::Key::Pause,
-                            LogicalKey::Key(Key::NumpadInsert) => imgui::Key::Keypad0,
-                            LogicalKey::Key(Key::NumpadEnd) => imgui::Key::Keypad1,
-                            LogicalKey::Key(Key::NumpadDownArrow) => imgui::Key::Keypad2,
-                            LogicalKey::Key(Key::NumpadPageDown) => imgui::Key::Keypad3,
-                            LogicalKey::Key(Key::NumpadLeftArrow) => imgui::Key::Keypad4,
-                            LogicalKey::Key(Key::NumpadBegin) => imgui::Key::Keypad5,
-                            LogicalKey::Key(Key::NumpadRightArrow) => imgui::Key::Keypad6,
-                            LogicalKey::Key(Key::NumpadHome) => imgui::Key::Keypad7,
-                            LogicalKey::Key(Key::NumpadUpArrow) => imgui::Key::Keypad8,
-                            LogicalKey::Key(Key::NumpadPageUp) => imgui::Key::Keypad9,
-                            LogicalKey::Key(Key::NumpadDecimal) => imgui::Key::KeypadDecimal,
-                            LogicalKey::Key(Key::NumpadDivide) => imgui::Key::KeypadDivide,
-                            LogicalKey::Key(Key::NumpadMultiply) => imgui::Key::KeypadMultiply,
-                            LogicalKey::Key(Key::NumpadSubtract) => imgui::Key::KeypadSubtract,
-                            LogicalKey::Key(Key::NumpadAdd) => imgui::Key::KeypadAdd,
-                            LogicalKey::Key(Key::NumpadEnter) => imgui::Key::KeypadEnter,
-                            // LogicalKey::Key(Key::Tab) => imgui::Key::KeypadEqual,
-                            // LogicalKey::Key(Key::Tab) => imgui::Key::ModShortcut,
-                            _ => return,
-                        };
-
-                        io.add_key_event(key, down);
-                    }
-                    WindowEvent::PointerMoved(x, y) => {
-                        io.add_mouse_pos_event([x as f32, y as f32]);
-                    }
-                    WindowEvent::PointerButton { down, button } => {
-                        let button = match button {
-                            PointerButton::Left => imgui::MouseButton::Left,
-                            PointerButton::Right => imgui::MouseButton::Right,
-                            PointerButton::Middle => imgui::MouseButton::Middle,
-                            PointerButton::Forward => imgui::MouseButton::Extra2,
-                            PointerButton::Back => imgui::MouseButton::Extra1,
-                            PointerButton::Unknown(_) => return,
-                        };
-                        io.add_mouse_button_event(button, down);
-                    }
-                    WindowEvent::Scroll { direction, value } => {
-                        let value = value as f32 / 26.0;
-                        let wheel = match direction {
-                            ScrollDirection::Vertical => [0.0, value],
-                            ScrollDirection::Horizontal => [value, 0.0],
-                        };
-                        io.add_mouse_wheel_event(wheel);
-                    }
-                    _ => {}
+    /// Feeds an event of a viewport window into imgui.
+    ///
+    /// Resizes, scale changes and close requests are passed on through the
+    /// viewport's platform data; focus changes are handled like those of the
+    /// main window. [`handle_event`](Self::handle_event) calls this for
+    /// waywin windows; it is exposed for hosts whose
+    /// [`ViewportHost::Window`] is something else.
+    #[cfg(feature = "docking")]
+    pub fn handle_viewport_event(
+        &mut self,
+        imgui: &mut Context,
+        viewport: imgui::Id,
+        event: WindowEvent,
+    ) -> EventResponse {
+        let Some(viewports) = &self.viewports else {
+            return EventResponse::Ignored;
+        };
+        let origin = viewports
+            .borrow_mut()
+            .handle_window_event(imgui, viewport, &event);
+        let Some(origin) = origin else {
+            return EventResponse::Ignored;
+        };
+        let io = imgui.io_mut();
+        match event {
+            WindowEvent::Focus(focus) => self.focus_changed(io, focus),
+            event => self.handle_input(io, event, origin),
+        }
+    }
+
+    /// Tells imgui that one of its windows gained or lost the keyboard
+    /// focus, releasing held input when it is lost.
+    fn focus_changed(&mut self, io: &mut Io, focus: bool) -> EventResponse {
+        let mut input = Input::new(io, self.recorder.as_mut(), &mut self.clock);
+        if !focus {
+            self.held.release_all(&mut input);
+            self.modifiers.focus_lost();
+            self.compose.reset();
+            self.ime.set_compose_preedit(None);
+        }
+        input.send(InputEvent::Focus(focus));
+        EventResponse::Ignored
+    }
+
+    /// Translates an input event of a window whose top-left corner is at
+    /// `origin` in imgui's coordinates.
+    fn handle_input(&mut self, io: &mut Io, event: WindowEvent, origin: [f32; 2]) -> EventResponse {
+        let mut input = Input::new(io, self.recorder.as_mut(), &mut self.clock);
+        match event {
+            WindowEvent::Key {
+                down,
+                physical_key,
+                logical_key: _,
+                text,
+                text_raw: _,
+                logical_key_unmodified,
+            } => {
+                let text = self
+                    .compose
+                    .feed_text(self.text_filter.filter(&text, self.modifiers.current()));
+                self.ime.set_compose_preedit(self.compose.preedit());
+                let response = EventResponse::keyboard(input.io, !text.is_empty());
+                for char in text {
+                    input.send(InputEvent::Character(char));
                 }
+
+                self.modifiers.key_event(
+                    &mut input,
+                    &mut self.held,
+                    logical_key_unmodified.as_ref(),
+                    down,
+                );
+
+                let Some(key) = self.key_mapping.translate(
+                    &self.key_map,
+                    physical_key.into(),
+                    logical_key_unmodified.as_ref(),
+                ) else {
+                    return response;
+                };
+
+                self.held.add_key_event(&mut input, key, down);
+                response
+            }
+            WindowEvent::PointerMoved(x, y) => self
+                .pointer
+                .moved(&mut input, [origin[0] + x as f32, origin[1] + y as f32]),
+            WindowEvent::PointerButton { down, button } => {
+                let response = EventResponse::pointer(input.io);
+                input.send(InputEvent::MouseSource(imgui::MouseSource::Mouse));
+                let button = match button {
+                    PointerButton::Left => imgui::MouseButton::Left,
+                    PointerButton::Right => imgui::MouseButton::Right,
+                    PointerButton::Middle => imgui::MouseButton::Middle,
+                    PointerButton::Forward => imgui::MouseButton::Extra2,
+                    PointerButton::Back => imgui::MouseButton::Extra1,
+                    PointerButton::Unknown(code) => {
+                        match self.extra_buttons.translate(code, down) {
+                            Some(button) => button,
+                            None => return response,
+                        }
+                    }
+                };
+                self.held.add_mouse_button_event(&mut input, button, down);
+                response
+            }
+            WindowEvent::Scroll { direction, value } => {
+                // waywin reports scrolling in pixels without its source
+                let axis = match direction {
+                    ScrollDirection::Vertical => ScrollAxis::Vertical,
+                    ScrollDirection::Horizontal => ScrollAxis::Horizontal,
+                };
+                self.scroll.handle_event(
+                    &mut input,
+                    ScrollEvent::Continuous {
+                        axis,
+                        value: value as f64,
+                        source: ScrollSource::Continuous,
+                    },
+                )
             }
-            _ => {}
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    pub fn prepare_frame<W: PlatformWindow + ?Sized>(&mut self, imgui: &mut Context, window: &W) {
+        #[cfg(feature = "docking")]
+        if let Some(viewports) = &self.viewports {
+            viewports
+                .borrow_mut()
+                .update_monitors(imgui, window.logical_size(), window.scale());
+        }
+        #[cfg(not(feature = "docking"))]
+        let _ = window;
+
+        self.pen.clear_samples();
+
+        let io = imgui.io_mut();
+        io.update_delta_time(self.clock.tick());
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(self.clock.now(), io);
+        }
+
+        if io.want_set_mouse_pos {
+            // with viewports, imgui's coordinates are on the virtual desktop
+            #[cfg(feature = "docking")]
+            let origin = imgui.main_viewport().pos;
+            #[cfg(not(feature = "docking"))]
+            let origin = [0.0, 0.0];
+            let [x, y] = imgui.io().mouse_pos;
+            self.cursor
+                .warp((x - origin[0]) as f64, (y - origin[1]) as f64);
         }
     }
 
-    pub fn prepare_frame(&mut self, _imgui: &mut Context, _window: &Window) {}
+    pub fn prepare_render<W: PlatformWindow + ?Sized>(&mut self, ui: &Ui, _window: &W) {
+        let cursor = if ui.io().mouse_draw_cursor {
+            None
+        } else {
+            ui.mouse_cursor().map(CursorShape::from)
+        };
+        self.cursor.update(cursor);
 
-    pub fn prepare_render(&mut self, _ui: &Ui, _window: &Window) {}
+        self.ime.prepare_render(ui);
+    }
 }
@@ -0,0 +1,162 @@
+use crate::{held::HeldInputs, record::Input};
+use std::ops::BitOr;
+use waywin::event::{Key, LogicalKey};
+
+/// A set of modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub caps_lock: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub num_lock: bool,
+    /// The Super or Logo key.
+    pub logo: bool,
+}
+impl Modifiers {
+    /// Decodes an xkb modifier mask, assuming the standard modifier indices
+    /// used by every common keymap: Shift, Lock, Control, Mod1 (Alt),
+    /// Mod2 (NumLock) and Mod4 (Super).
+    pub fn from_xkb_mask(mask: u32) -> Self {
+        let bit = |index: u32| mask & (1 << index) != 0;
+        Self {
+            shift: bit(0),
+            caps_lock: bit(1),
+            ctrl: bit(2),
+            alt: bit(3),
+            num_lock: bit(4),
+            logo: bit(6),
+        }
+    }
+
+    /// Whether any modifier is set in both `self` and `other`.
+    pub fn intersects(self, other: Self) -> bool {
+        (self.shift && other.shift)
+            || (self.caps_lock && other.caps_lock)
+            || (self.ctrl && other.ctrl)
+            || (self.alt && other.alt)
+            || (self.num_lock && other.num_lock)
+            || (self.logo && other.logo)
+    }
+}
+impl BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            shift: self.shift | rhs.shift,
+            caps_lock: self.caps_lock | rhs.caps_lock,
+            ctrl: self.ctrl | rhs.ctrl,
+            alt: self.alt | rhs.alt,
+            num_lock: self.num_lock | rhs.num_lock,
+            logo: self.logo | rhs.logo,
+        }
+    }
+}
+
+/// The keyboard's modifier state, as delivered by `wl_keyboard.modifiers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModifierState {
+    /// Modifiers whose keys are held.
+    pub depressed: Modifiers,
+    /// Modifiers that apply to the next key press, e.g. with sticky keys.
+    pub latched: Modifiers,
+    /// Modifiers that stay active until toggled off, e.g. Caps Lock.
+    pub locked: Modifiers,
+}
+impl ModifierState {
+    /// The modifiers that are in effect.
+    pub fn effective(&self) -> Modifiers {
+        self.depressed | self.latched | self.locked
+    }
+}
+
+/// Keeps imgui's `Mod*` keys in sync with the keyboard.
+///
+/// Until the host reports the compositor's modifier state, modifiers are
+/// inferred from the left and right modifier keys, which are tracked
+/// separately so releasing one side keeps the modifier held by the other.
+pub(crate) struct ModifierTracker {
+    authoritative: bool,
+    /// Left and right keys of Shift, Ctrl, Alt and Super.
+    sides: [[bool; 2]; 4],
+    current: Modifiers,
+}
+impl ModifierTracker {
+    pub fn new() -> Self {
+        Self {
+            authoritative: false,
+            sides: [[false; 2]; 4],
+            current: Modifiers::default(),
+        }
+    }
+
+    pub fn current(&self) -> Modifiers {
+        self.current
+    }
+
+    pub fn set_state(&mut self, input: &mut Input, held: &mut HeldInputs, state: ModifierState) {
+        self.authoritative = true;
+        self.update(input, held, state.effective());
+    }
+
+    pub fn key_event(
+        &mut self,
+        input: &mut Input,
+        held: &mut HeldInputs,
+        key: LogicalKey<&str>,
+        down: bool,
+    ) {
+        if self.authoritative {
+            return;
+        }
+        let (modifier, side) = match key {
+            LogicalKey::Key(Key::LShift | Key::Shift) => (0, 0),
+            LogicalKey::Key(Key::RShift) => (0, 1),
+            LogicalKey::Key(Key::LCtrl | Key::Ctrl) => (1, 0),
+            LogicalKey::Key(Key::RCtrl) => (1, 1),
+            LogicalKey::Key(Key::LAlt | Key::Alt) => (2, 0),
+            LogicalKey::Key(Key::RAlt) => (2, 1),
+            LogicalKey::Key(Key::LSuper | Key::Super) => (3, 0),
+            LogicalKey::Key(Key::RSuper) => (3, 1),
+            _ => return,
+        };
+        self.sides[modifier][side] = down;
+
+        let held_down = |modifier: usize| self.sides[modifier].contains(&true);
+        let modifiers = Modifiers {
+            shift: held_down(0),
+            ctrl: held_down(1),
+            alt: held_down(2),
+            logo: held_down(3),
+            ..self.current
+        };
+        self.update(input, held, modifiers);
+    }
+
+    /// Forgets held modifier keys after [`HeldInputs::release_all`] released
+    /// them in imgui. Lock state is kept.
+    pub fn focus_lost(&mut self) {
+        self.sides = [[false; 2]; 4];
+        self.current = Modifiers {
+            caps_lock: self.current.caps_lock,
+            num_lock: self.current.num_lock,
+            ..Modifiers::default()
+        };
+    }
+
+    fn update(&mut self, input: &mut Input, held: &mut HeldInputs, modifiers: Modifiers) {
+        let changes = [
+            (self.current.ctrl, modifiers.ctrl, imgui::Key::ModCtrl),
+            (self.current.shift, modifiers.shift, imgui::Key::ModShift),
+            (self.current.alt, modifiers.alt, imgui::Key::ModAlt),
+            (self.current.logo, modifiers.logo, imgui::Key::ModSuper),
+        ];
+        for (old, new, key) in changes {
+            if old != new {
+                held.add_key_event(input, key, new);
+            }
+        }
+        self.current = modifiers;
+    }
+}
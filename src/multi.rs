@@ -0,0 +1,121 @@
+use crate::{EventResponse, PlatformWindow, WaywinPlatform};
+use imgui::{Context, SuspendedContext};
+use std::sync::Arc;
+use waywin::{
+    Window,
+    event::{WaywinEvent, WindowEvent},
+};
+
+/// Drives several windows, each bound to its own imgui context.
+///
+/// imgui allows only one active context at a time, so every context is kept
+/// suspended and only activated for the duration of a call. Contexts created
+/// with [`Context::create_with_shared_font_atlas`] share their fonts.
+///
+/// Windows are waywin windows by default; any [`PlatformWindow`], such as a
+/// [`HeadlessWindow`](crate::HeadlessWindow), works as well. Windows are
+/// picked with a predicate, e.g. `|window| window.id() == id`.
+pub struct WaywinMultiPlatform<W = Arc<Window>> {
+    windows: Vec<AttachedWindow<W>>,
+}
+
+struct AttachedWindow<W> {
+    window: W,
+    imgui: Option<SuspendedContext>,
+    platform: WaywinPlatform,
+}
+impl<W> AttachedWindow<W> {
+    fn with<R>(&mut self, f: impl FnOnce(&mut Context, &mut WaywinPlatform, &W) -> R) -> Option<R> {
+        let mut imgui = match self.imgui.take()?.activate() {
+            Ok(imgui) => imgui,
+            Err(suspended) => {
+                self.imgui = Some(suspended);
+                return None;
+            }
+        };
+        let result = f(&mut imgui, &mut self.platform, &self.window);
+        self.imgui = Some(imgui.suspend());
+        Some(result)
+    }
+}
+
+impl<W> Default for WaywinMultiPlatform<W> {
+    fn default() -> Self {
+        Self {
+            windows: Vec::new(),
+        }
+    }
+}
+
+impl<W: PlatformWindow> WaywinMultiPlatform<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches a window. A window that is already attached has to be
+    /// removed first.
+    ///
+    /// `imgui` must be the active context; it is suspended until it is needed.
+    pub fn add_window(&mut self, window: W, mut imgui: Context) {
+        let platform = WaywinPlatform::new(&mut imgui, &window);
+        self.windows.push(AttachedWindow {
+            window,
+            imgui: Some(imgui.suspend()),
+            platform,
+        });
+    }
+
+    /// Detaches the first window matching `is_window` and returns its
+    /// context.
+    pub fn remove_window(&mut self, is_window: impl FnMut(&W) -> bool) -> Option<SuspendedContext> {
+        let index = self.position(is_window)?;
+        self.windows.swap_remove(index).imgui
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &W> {
+        self.windows.iter().map(|attached| &attached.window)
+    }
+
+    /// Activates the context of the first window matching `is_window` and
+    /// runs `f` with it, e.g. to build and render a frame.
+    ///
+    /// Returns `None` if no window matches or another imgui context is
+    /// active.
+    pub fn with_window<R>(
+        &mut self,
+        is_window: impl FnMut(&W) -> bool,
+        f: impl FnOnce(&mut Context, &mut WaywinPlatform, &W) -> R,
+    ) -> Option<R> {
+        let index = self.position(is_window)?;
+        self.windows[index].with(f)
+    }
+
+    /// Feeds an event into the context of the first window matching
+    /// `is_window`.
+    pub fn handle_window_event(
+        &mut self,
+        is_window: impl FnMut(&W) -> bool,
+        event: WindowEvent,
+    ) -> EventResponse {
+        self.with_window(is_window, |imgui, platform, window| {
+            platform.handle_window_event(imgui, window, event)
+        })
+        .unwrap_or(EventResponse::Ignored)
+    }
+
+    fn position(&self, mut is_window: impl FnMut(&W) -> bool) -> Option<usize> {
+        self.windows
+            .iter()
+            .position(|attached| is_window(&attached.window))
+    }
+}
+
+impl WaywinMultiPlatform<Arc<Window>> {
+    /// Routes an event to the window it belongs to.
+    pub fn handle_event(&mut self, event: WaywinEvent) -> EventResponse {
+        let WaywinEvent::WindowEvent { event, window_id } = event else {
+            return EventResponse::Ignored;
+        };
+        self.handle_window_event(|window| window.id() == window_id, event)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    EventResponse,
+    held::HeldInputs,
+    record::{Input, InputEvent},
+};
+use imgui::{MouseButton, MouseSource};
+
+/// The end of the stylus in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PenTool {
+    #[default]
+    Pen,
+    Eraser,
+}
+
+/// Tablet tool events, as delivered by `zwp_tablet_tool_v2`.
+///
+/// Axis events update the current state; `Frame` ends a group of events
+/// that happened at the same time and records a [`PenSample`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PenEvent {
+    ProximityIn {
+        tool: PenTool,
+    },
+    ProximityOut,
+    /// Position in logical window coordinates.
+    Motion {
+        x: f64,
+        y: f64,
+    },
+    Down,
+    Up,
+    /// Normalised to `0.0..=1.0`.
+    Pressure(f64),
+    /// Tilt in degrees from the perpendicular.
+    Tilt {
+        x: f64,
+        y: f64,
+    },
+    /// A barrel button, numbered from 0.
+    Button {
+        button: u8,
+        down: bool,
+    },
+    Frame {
+        time: u32,
+    },
+}
+
+/// The state of the stylus at the end of a frame of tablet events.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PenSample {
+    pub tool: PenTool,
+    pub position: [f64; 2],
+    pub pressure: f64,
+    pub tilt: [f64; 2],
+    pub down: bool,
+    /// Bit `n` is set while barrel button `n` is held.
+    pub buttons: u32,
+    /// Timestamp in milliseconds, with an undefined base.
+    pub time: u32,
+}
+
+pub(crate) struct PenState {
+    current: Option<PenSample>,
+    samples: Vec<PenSample>,
+}
+impl PenState {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> Option<PenSample> {
+        self.current
+    }
+
+    pub fn samples(&self) -> &[PenSample] {
+        &self.samples
+    }
+
+    pub fn clear_samples(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn handle_event(
+        &mut self,
+        input: &mut Input,
+        held: &mut HeldInputs,
+        event: PenEvent,
+    ) -> EventResponse {
+        let response = EventResponse::pointer(input.io);
+        if let PenEvent::ProximityIn { tool } = event {
+            self.current = Some(PenSample {
+                tool,
+                ..Default::default()
+            });
+            return response;
+        }
+        let Some(pen) = &mut self.current else {
+            return EventResponse::Ignored;
+        };
+
+        input.send(InputEvent::MouseSource(MouseSource::Pen));
+        match event {
+            // handled above
+            PenEvent::ProximityIn { .. } => {}
+            PenEvent::ProximityOut => {
+                if pen.down {
+                    held.add_mouse_button_event(input, MouseButton::Left, false);
+                }
+                for button in 0..2 {
+                    if pen.buttons & (1 << button) != 0
+                        && let Some(button) = barrel_button(button)
+                    {
+                        held.add_mouse_button_event(input, button, false);
+                    }
+                }
+                input.send(InputEvent::MousePos([-f32::MAX, -f32::MAX]));
+                self.current = None;
+            }
+            PenEvent::Motion { x, y } => {
+                pen.position = [x, y];
+                input.send(InputEvent::MousePos([x as f32, y as f32]));
+            }
+            PenEvent::Down => {
+                pen.down = true;
+                held.add_mouse_button_event(input, MouseButton::Left, true);
+            }
+            PenEvent::Up => {
+                pen.down = false;
+                held.add_mouse_button_event(input, MouseButton::Left, false);
+            }
+            PenEvent::Pressure(pressure) => pen.pressure = pressure,
+            PenEvent::Tilt { x, y } => pen.tilt = [x, y],
+            PenEvent::Button { button, down } => {
+                if let Some(bit) = 1u32.checked_shl(button as u32) {
+                    if down {
+                        pen.buttons |= bit;
+                    } else {
+                        pen.buttons &= !bit;
+                    }
+                }
+                if let Some(button) = barrel_button(button) {
+                    held.add_mouse_button_event(input, button, down);
+                }
+            }
+            PenEvent::Frame { time } => {
+                pen.time = time;
+                self.samples.push(*pen);
+            }
+        }
+        response
+    }
+}
+
+/// The lower barrel button acts as a right click, the upper one as a middle
+/// click.
+fn barrel_button(button: u8) -> Option<MouseButton> {
+    match button {
+        0 => Some(MouseButton::Right),
+        1 => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
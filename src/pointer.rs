@@ -0,0 +1,75 @@
+use crate::{
+    EventResponse,
+    held::HeldInputs,
+    record::{Input, InputEvent},
+};
+use imgui::MouseSource;
+
+/// The pointer entering or leaving the main window, as delivered by
+/// `wl_pointer.enter` and `wl_pointer.leave`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEvent {
+    /// `position` is where the pointer entered, in logical window
+    /// coordinates. Without it, the position from before it left is restored
+    /// until the next motion event.
+    Enter {
+        position: Option<[f64; 2]>,
+    },
+    Leave,
+}
+
+/// Tells imgui where the pointer is, including when it is not over the
+/// window at all.
+pub(crate) struct PointerState {
+    /// Last position sent to imgui, in imgui's coordinates.
+    position: Option<[f32; 2]>,
+    inside: bool,
+}
+impl PointerState {
+    pub fn new() -> Self {
+        Self {
+            position: None,
+            inside: true,
+        }
+    }
+
+    pub fn moved(&mut self, input: &mut Input, position: [f32; 2]) -> EventResponse {
+        self.position = Some(position);
+        self.inside = true;
+        input.send(InputEvent::MouseSource(MouseSource::Mouse));
+        input.send(InputEvent::MousePos(position));
+        EventResponse::pointer(input.io)
+    }
+
+    pub fn handle_event(
+        &mut self,
+        input: &mut Input,
+        held: &mut HeldInputs,
+        event: PointerEvent,
+    ) -> EventResponse {
+        let response = EventResponse::pointer(input.io);
+        match event {
+            PointerEvent::Enter { position } => {
+                self.inside = true;
+                let position = position
+                    .map(|[x, y]| [x as f32, y as f32])
+                    .or(self.position);
+                if let Some(position) = position {
+                    self.moved(input, position);
+                }
+            }
+            PointerEvent::Leave => {
+                if !self.inside {
+                    return EventResponse::Ignored;
+                }
+                self.inside = false;
+                // the implicit grab normally keeps the pointer here until the
+                // buttons are released; release them anyway in case the grab
+                // was lost, e.g. to a popup or a compositor quirk
+                held.release_buttons(input);
+                input.send(InputEvent::MousePos([-f32::MAX, -f32::MAX]));
+            }
+        }
+        response
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{held, time::FrameClock};
+use imgui::{Context, Io, Key, MouseButton, MouseSource};
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+const HEADER: &str = "# imgui-waywin-support recording 1";
+
+/// An input event as fed into imgui, after translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    Key { key: Key, down: bool },
+    Character(char),
+    MousePos([f32; 2]),
+    MouseButton { button: MouseButton, down: bool },
+    MouseWheel([f32; 2]),
+    MouseSource(MouseSource),
+    Focus(bool),
+}
+impl InputEvent {
+    /// Queues the event on `io`.
+    pub fn apply(self, io: &mut Io) {
+        match self {
+            Self::Key { key, down } => io.add_key_event(key, down),
+            Self::Character(char) => io.add_input_character(char),
+            Self::MousePos(pos) => io.add_mouse_pos_event(pos),
+            Self::MouseButton { button, down } => io.add_mouse_button_event(button, down),
+            Self::MouseWheel(wheel) => io.add_mouse_wheel_event(wheel),
+            Self::MouseSource(source) => io.add_mouse_source_event(source),
+            Self::Focus(focused) => held::add_focus_event(io, focused),
+        }
+    }
+}
+
+/// Feeds translated events into imgui, recording them if a recording is
+/// running.
+pub(crate) struct Input<'a> {
+    pub io: &'a mut Io,
+    recorder: Option<(&'a mut Recorder, Duration)>,
+}
+impl<'a> Input<'a> {
+    pub fn new(io: &'a mut Io, recorder: Option<&'a mut Recorder>, clock: &mut FrameClock) -> Self {
+        Self {
+            io,
+            recorder: recorder.map(|recorder| (recorder, clock.now())),
+        }
+    }
+
+    pub fn send(&mut self, event: InputEvent) {
+        event.apply(self.io);
+        if let Some((recorder, now)) = &mut self.recorder {
+            recorder.event(*now, event);
+        }
+    }
+}
+
+/// Writes a recording as events are fed into imgui.
+pub(crate) struct Recorder {
+    writer: Box<dyn Write>,
+    frame: u64,
+    start: Duration,
+    /// The first write error; later events are dropped.
+    error: Option<io::Error>,
+}
+impl Recorder {
+    pub fn new(writer: Box<dyn Write>, start: Duration) -> Self {
+        let mut recorder = Self {
+            writer,
+            frame: 0,
+            start,
+            error: None,
+        };
+        recorder.write(format_args!("{HEADER}"));
+        recorder
+    }
+
+    pub fn event(&mut self, now: Duration, event: InputEvent) {
+        let time = now.saturating_sub(self.start).as_secs_f64();
+        let frame = self.frame;
+        self.write(format_args!("{frame} {time:.6} {}", EventLine(event)));
+    }
+
+    /// Records the timing and display state of a frame, ending the group of
+    /// events that belong to it.
+    pub fn frame(&mut self, now: Duration, io: &Io) {
+        let time = now.saturating_sub(self.start).as_secs_f64();
+        let frame = self.frame;
+        let [w, h] = io.display_size;
+        let [sx, sy] = io.display_framebuffer_scale;
+        self.write(format_args!(
+            "{frame} {time:.6} frame {} {w} {h} {sx} {sy}",
+            io.delta_time
+        ));
+        self.frame += 1;
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+
+    fn write(&mut self, line: fmt::Arguments<'_>) {
+        if self.error.is_none()
+            && let Err(err) = writeln!(self.writer, "{line}")
+        {
+            self.error = Some(err);
+        }
+    }
+}
+
+pub(crate) fn create_file(path: &Path) -> io::Result<Box<dyn Write>> {
+    Ok(Box::new(BufWriter::new(File::create(path)?)))
+}
+
+struct EventLine(InputEvent);
+impl fmt::Display for EventLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = |down| if down { "down" } else { "up" };
+        match self.0 {
+            InputEvent::Key { key, down } => write!(f, "key {key:?} {}", state(down)),
+            InputEvent::Character(char) => write!(f, "char U+{:04X}", char as u32),
+            InputEvent::MousePos([x, y]) => write!(f, "pos {x} {y}"),
+            InputEvent::MouseButton { button, down } => {
+                write!(f, "button {button:?} {}", state(down))
+            }
+            InputEvent::MouseWheel([x, y]) => write!(f, "wheel {x} {y}"),
+            InputEvent::MouseSource(source) => write!(f, "source {source:?}"),
+            InputEvent::Focus(focused) => write!(f, "focus {}", if focused { "in" } else { "out" }),
+        }
+    }
+}
+
+/// One frame of a [`Recording`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    pub frame: u64,
+    /// When the frame started, relative to the start of the recording.
+    pub time: Duration,
+    pub delta_time: f32,
+    pub display_size: [f32; 2],
+    pub display_framebuffer_scale: [f32; 2],
+    /// Events fed into imgui before the frame, in order.
+    pub events: Vec<(Duration, InputEvent)>,
+}
+
+/// Input recorded by [`WaywinPlatform::start_recording`](crate::WaywinPlatform::start_recording).
+///
+/// Recordings are stored as text, one event per line, each prefixed with
+/// the frame it belongs to and the time in seconds since the recording
+/// started:
+///
+/// ```text
+/// # imgui-waywin-support recording 1
+/// 0 0.000000 frame 0.016666668 800 600 1 1
+/// 1 0.010000 source Mouse
+/// 1 0.010000 pos 120 48.5
+/// 1 0.020000 button Left down
+/// 1 0.020000 char U+0061
+/// 1 0.033333 frame 0.016666668 800 600 1 1
+/// ```
+///
+/// Events recorded after the last frame are dropped, as imgui never saw them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Recording {
+    pub frames: Vec<RecordedFrame>,
+}
+impl Recording {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for frame in &self.frames {
+            for (time, event) in &frame.events {
+                let time = time.as_secs_f64();
+                writeln!(f, "{} {time:.6} {}", frame.frame, EventLine(*event))?;
+            }
+            let [w, h] = frame.display_size;
+            let [sx, sy] = frame.display_framebuffer_scale;
+            writeln!(
+                f,
+                "{} {:.6} frame {} {w} {h} {sx} {sy}",
+                frame.frame,
+                frame.time.as_secs_f64(),
+                frame.delta_time
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRecordingError {
+    /// 1-based line number.
+    pub line: usize,
+    pub reason: &'static str,
+}
+impl fmt::Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+impl Error for ParseRecordingError {}
+
+impl FromStr for Recording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut recording = Self::default();
+        let mut events = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let error = |reason| ParseRecordingError {
+                line: index + 1,
+                reason,
+            };
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let mut next = |reason| fields.next().ok_or_else(|| error(reason));
+            let frame: u64 = next("expected a frame number")?
+                .parse()
+                .map_err(|_| error("invalid frame number"))?;
+            let time = next("expected a timestamp")?
+                .parse()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| error("invalid timestamp"))?;
+            let kind = next("expected an event")?;
+            let args: Vec<&str> = fields.collect();
+
+            let float = |i: usize| -> Result<f32, ParseRecordingError> {
+                args.get(i)
+                    .and_then(|arg| arg.parse().ok())
+                    .ok_or_else(|| error("invalid number"))
+            };
+            let down = |i: usize| match args.get(i) {
+                Some(&"down") => Ok(true),
+                Some(&"up") => Ok(false),
+                _ => Err(error("expected `down` or `up`")),
+            };
+            let event = match kind {
+                "frame" => {
+                    if recording
+                        .frames
+                        .last()
+                        .is_some_and(|last| last.frame >= frame)
+                    {
+                        return Err(error("frames out of order"));
+                    }
+                    recording.frames.push(RecordedFrame {
+                        frame,
+                        time,
+                        delta_time: float(0)?,
+                        display_size: [float(1)?, float(2)?],
+                        display_framebuffer_scale: [float(3)?, float(4)?],
+                        events: std::mem::take(&mut events),
+                    });
+                    continue;
+                }
+                "key" => InputEvent::Key {
+                    key: Key::VARIANTS
+                        .into_iter()
+                        .find(|key| args.first() == Some(&format!("{key:?}").as_str()))
+                        .ok_or_else(|| error("unknown imgui key"))?,
+                    down: down(1)?,
+                },
+                "char" => InputEvent::Character(
+                    args.first()
+                        .and_then(|arg| arg.strip_prefix("U+"))
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| error("invalid character"))?,
+                ),
+                "pos" => InputEvent::MousePos([float(0)?, float(1)?]),
+                "button" => InputEvent::MouseButton {
+                    button: MouseButton::VARIANTS
+                        .into_iter()
+                        .find(|button| args.first() == Some(&format!("{button:?}").as_str()))
+                        .ok_or_else(|| error("unknown mouse button"))?,
+                    down: down(1)?,
+                },
+                "wheel" => InputEvent::MouseWheel([float(0)?, float(1)?]),
+                "source" => InputEvent::MouseSource(match args.first() {
+                    Some(&"Mouse") => MouseSource::Mouse,
+                    Some(&"TouchScreen") => MouseSource::TouchScreen,
+                    Some(&"Pen") => MouseSource::Pen,
+                    _ => return Err(error("unknown mouse source")),
+                }),
+                "focus" => InputEvent::Focus(match args.first() {
+                    Some(&"in") => true,
+                    Some(&"out") => false,
+                    _ => return Err(error("expected `in` or `out`")),
+                }),
+                _ => return Err(error("unknown event")),
+            };
+            if recording
+                .frames
+                .last()
+                .is_some_and(|last| last.frame >= frame)
+            {
+                return Err(error("event belongs to a finished frame"));
+            }
+            events.push((time, event));
+        }
+        Ok(recording)
+    }
+}
+
+/// Feeds a [`Recording`] into an imgui context, one frame at a time.
+///
+/// No window is needed, which makes recordings usable as reproductions in
+/// tests and CI:
+///
+/// ```no_run
+/// # fn run(imgui: &mut imgui::Context) -> std::io::Result<()> {
+/// let mut replay = imgui_waywin_support::Replay::load("bug.rec")?;
+/// while replay.prepare_frame(imgui) {
+///     let ui = imgui.new_frame();
+///     // build the same UI as the recorded application
+///     # let _ = ui;
+///     imgui.render();
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Replay {
+    frames: std::vec::IntoIter<RecordedFrame>,
+    frame: Option<u64>,
+}
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            frames: recording.frames.into_iter(),
+            frame: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Recording::load(path).map(Self::new)
+    }
+
+    /// The number of the frame last prepared.
+    pub fn frame(&self) -> Option<u64> {
+        self.frame
+    }
+
+    /// Queues the input of the next recorded frame and restores its timing
+    /// and display size. Call it in place of
+    /// [`WaywinPlatform::prepare_frame`](crate::WaywinPlatform::prepare_frame).
+    ///
+    /// Returns `false` once the recording is exhausted.
+    pub fn prepare_frame(&mut self, imgui: &mut Context) -> bool {
+        let Some(frame) = self.frames.next() else {
+            return false;
+        };
+        let io = imgui.io_mut();
+        for (_, event) in frame.events {
+            event.apply(io);
+        }
+        io.display_size = frame.display_size;
+        io.display_framebuffer_scale = frame.display_framebuffer_scale;
+        io.delta_time = frame.delta_time;
+        self.frame = Some(frame.frame);
+        true
+    }
+}
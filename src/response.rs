@@ -0,0 +1,57 @@
+use imgui::Io;
+
+/// The kind of input an event carried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    Pointer,
+    Keyboard,
+    /// A key event that produced text while a text field was focused.
+    Text,
+}
+
+/// What imgui made of an event passed to
+/// [`WaywinPlatform::handle_event`](crate::WaywinPlatform::handle_event).
+///
+/// Capture is decided from the `want_capture_*` flags of the last frame, as
+/// recommended by imgui.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventResponse {
+    /// imgui wants this input; the application should not act on it.
+    Consumed(EventCategory),
+    /// imgui saw this input but does not need it exclusively.
+    PassThrough(EventCategory),
+    /// Not an input event, or not for this window.
+    Ignored,
+}
+impl EventResponse {
+    pub fn consumed(self) -> bool {
+        matches!(self, Self::Consumed(_))
+    }
+
+    pub fn category(self) -> Option<EventCategory> {
+        match self {
+            Self::Consumed(category) | Self::PassThrough(category) => Some(category),
+            Self::Ignored => None,
+        }
+    }
+
+    pub(crate) fn pointer(io: &Io) -> Self {
+        Self::new(EventCategory::Pointer, io.want_capture_mouse)
+    }
+
+    pub(crate) fn keyboard(io: &Io, has_text: bool) -> Self {
+        if has_text && io.want_text_input {
+            Self::Consumed(EventCategory::Text)
+        } else {
+            Self::new(EventCategory::Keyboard, io.want_capture_keyboard)
+        }
+    }
+
+    fn new(category: EventCategory, consumed: bool) -> Self {
+        if consumed {
+            Self::Consumed(category)
+        } else {
+            Self::PassThrough(category)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::PlatformWindow;
+use imgui::{Context, FontAtlas, Io, Style};
+
+/// Rebuilds the font atlas for a scale factor, e.g. by adding the fonts again
+/// at `size * scale` pixels.
+pub type FontBuilder = Box<dyn FnMut(&mut FontAtlas, f32)>;
+
+/// What to adjust when the window's scale factor changes.
+///
+/// By default nothing is, and imgui renders logical-sized geometry that the
+/// renderer stretches by `io.display_framebuffer_scale`.
+#[derive(Default)]
+pub struct ScalePolicy {
+    /// Scales paddings, spacings, roundings, etc. with [`imgui::Style::scale_all_sizes`].
+    ///
+    /// Sizes are always scaled from the style as it was when scaling was
+    /// first applied, so rounding errors do not build up over scale changes.
+    /// Colour changes made in the meantime are kept.
+    pub scale_style: bool,
+    /// Rasterises fonts at the physical size so text stays crisp.
+    ///
+    /// The atlas is cleared before the builder runs and `io.font_global_scale`
+    /// is set to `1.0 / scale`, so fonts keep their logical size. The host must
+    /// upload the new atlas to its renderer afterwards, see
+    /// [`WaywinPlatform::fonts_rebuilt`](crate::WaywinPlatform::fonts_rebuilt).
+    /// When a policy without a builder replaces it, `io.font_global_scale`
+    /// goes back to `1.0` and the atlas is left as the builder made it.
+    pub rebuild_fonts: Option<FontBuilder>,
+}
+
+pub(crate) struct ScaleState {
+    scale: f32,
+    policy: ScalePolicy,
+    /// Unscaled style, and the scale the current style was derived with.
+    base_style: Option<(Style, f32)>,
+    /// Scale the font atlas was last built for.
+    font_scale: Option<f32>,
+    fonts_rebuilt: bool,
+}
+impl ScaleState {
+    pub fn new(window: &(impl PlatformWindow + ?Sized)) -> Self {
+        Self {
+            scale: window.scale(),
+            policy: ScalePolicy::default(),
+            base_style: None,
+            font_scale: None,
+            fonts_rebuilt: false,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, imgui: &mut Context, scale: f32) {
+        self.scale = scale;
+        self.apply(imgui);
+    }
+
+    pub fn set_policy(&mut self, imgui: &mut Context, policy: ScalePolicy) {
+        self.policy = policy;
+        // a new builder has to run even at the same scale
+        if self.font_scale.take().is_some() {
+            imgui.io_mut().font_global_scale = 1.0;
+        }
+        self.apply(imgui);
+    }
+
+    pub fn take_fonts_rebuilt(&mut self) -> bool {
+        std::mem::take(&mut self.fonts_rebuilt)
+    }
+
+    fn apply(&mut self, imgui: &mut Context) {
+        let style_scale = self.base_style.as_ref().map_or(1.0, |&(_, scale)| scale);
+        if self.policy.scale_style && style_scale != self.scale {
+            let style = imgui.style_mut();
+            let (base, scale) = self.base_style.get_or_insert((*style, 1.0));
+            let colors = style.colors;
+            *style = *base;
+            style.scale_all_sizes(self.scale);
+            style.colors = colors;
+            *scale = self.scale;
+        } else if !self.policy.scale_style
+            && let Some((base, _)) = self.base_style.take()
+        {
+            let style = imgui.style_mut();
+            let colors = style.colors;
+            *style = base;
+            style.colors = colors;
+        }
+
+        if let Some(rebuild_fonts) = &mut self.policy.rebuild_fonts
+            && self.font_scale != Some(self.scale)
+        {
+            let fonts = imgui.fonts();
+            fonts.clear();
+            rebuild_fonts(fonts, self.scale);
+            imgui.io_mut().font_global_scale = 1.0 / self.scale;
+            self.font_scale = Some(self.scale);
+            self.fonts_rebuilt = true;
+        }
+    }
+}
+
+/// Sets the display size in logical pixels and the framebuffer scale.
+///
+/// The framebuffer scale is derived from the physical size where possible so
+/// that fractional scales round the same way the window's buffer does.
+pub(crate) fn update_display(io: &mut Io, window: &(impl PlatformWindow + ?Sized)) {
+    let [lw, lh] = window.logical_size();
+    let [pw, ph] = window.physical_size();
+    let scale = window.scale();
+
+    io.display_size = [lw, lh];
+    io.display_framebuffer_scale = [
+        if lw > 0.0 { pw / lw } else { scale },
+        if lh > 0.0 { ph / lh } else { scale },
+    ];
+}
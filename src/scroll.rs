@@ -0,0 +1,135 @@
+use crate::{
+    EventResponse,
+    record::{Input, InputEvent},
+};
+use imgui::MouseSource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollAxis {
+    Vertical,
+    Horizontal,
+}
+impl ScrollAxis {
+    /// Index into per-axis `[horizontal, vertical]` arrays.
+    fn index(self) -> usize {
+        match self {
+            Self::Horizontal => 0,
+            Self::Vertical => 1,
+        }
+    }
+}
+
+/// What produced continuous scrolling, following `wl_pointer.axis_source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollSource {
+    /// Fingers on a touchpad or touchscreen.
+    Finger,
+    /// Other continuous devices, e.g. a trackpoint or button scrolling.
+    Continuous,
+}
+
+/// Scroll events as delivered by `wl_pointer`.
+///
+/// Positive values scroll content towards the top left, like imgui's wheel.
+/// Steps are passed to imgui in 1/120ths; smaller amounts are carried over
+/// to the next event of the same scroll sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollEvent {
+    /// Mouse wheel movement in 1/120ths of a detent, so high-resolution
+    /// wheels report fractions of a step.
+    Wheel { axis: ScrollAxis, value120: i32 },
+    /// Continuous movement in logical pixels.
+    Continuous {
+        axis: ScrollAxis,
+        value: f64,
+        source: ScrollSource,
+    },
+    /// The fingers were lifted, or kinetic scrolling came to a halt,
+    /// following `wl_pointer.axis_stop`. Ends the scroll sequence on `axis`.
+    Stop { axis: ScrollAxis },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollSettings {
+    /// Multiplier for horizontal and vertical wheel steps.
+    pub sensitivity: [f32; 2],
+    /// Inverts both axes, so content follows the fingers.
+    pub natural: bool,
+    /// Logical pixels of continuous scrolling that make one wheel step.
+    /// Values that are not positive fall back to the default.
+    pub pixels_per_step: f32,
+}
+impl Default for ScrollSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: [1.0, 1.0],
+            natural: false,
+            pixels_per_step: 26.0,
+        }
+    }
+}
+
+pub(crate) struct ScrollState {
+    pub settings: ScrollSettings,
+    /// Per axis, the source of the scroll sequence in progress; `None` for
+    /// wheels and once a sequence stopped.
+    sources: [Option<ScrollSource>; 2],
+    /// Per axis, steps not passed to imgui yet.
+    remainders: [f32; 2],
+}
+impl ScrollState {
+    pub fn new() -> Self {
+        Self {
+            settings: ScrollSettings::default(),
+            sources: [None; 2],
+            remainders: [0.0; 2],
+        }
+    }
+
+    pub fn handle_event(&mut self, input: &mut Input, event: ScrollEvent) -> EventResponse {
+        let (axis, source, steps) = match event {
+            ScrollEvent::Wheel { axis, value120 } => {
+                input.send(InputEvent::MouseSource(MouseSource::Mouse));
+                (axis, None, value120 as f32 / 120.0)
+            }
+            ScrollEvent::Continuous {
+                axis,
+                value,
+                source,
+            } => {
+                input.send(InputEvent::MouseSource(match source {
+                    ScrollSource::Finger => MouseSource::TouchScreen,
+                    ScrollSource::Continuous => MouseSource::Mouse,
+                }));
+                let pixels_per_step = if self.settings.pixels_per_step > 0.0 {
+                    self.settings.pixels_per_step
+                } else {
+                    ScrollSettings::default().pixels_per_step
+                };
+                (axis, Some(source), value as f32 / pixels_per_step)
+            }
+            ScrollEvent::Stop { axis } => {
+                self.sources[axis.index()] = None;
+                self.remainders[axis.index()] = 0.0;
+                return EventResponse::pointer(input.io);
+            }
+        };
+
+        let i = axis.index();
+        if self.sources[i] != source {
+            // a new sequence starts from whole steps
+            self.sources[i] = source;
+            self.remainders[i] = 0.0;
+        }
+        let sign = if self.settings.natural { -1.0 } else { 1.0 };
+        let steps = self.remainders[i] + sign * self.settings.sensitivity[i] * steps;
+        let delivered = (steps * 120.0).trunc() / 120.0;
+        self.remainders[i] = steps - delivered;
+        if delivered != 0.0 {
+            let mut wheel = [0.0; 2];
+            wheel[i] = delivered;
+            input.send(InputEvent::MouseWheel(wheel));
+        }
+        EventResponse::pointer(input.io)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{Canvas, PlatformWindow, SoftwareRenderer, software::premultiply};
+use imgui::{Context, DrawData};
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    os::{
+        fd::{AsFd, BorrowedFd},
+        unix::fs::FileExt,
+    },
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use waywin::Window;
+
+/// `wl_shm.format.argb8888`, the format of every [`ShmBuffer`].
+pub const SHM_FORMAT_ARGB8888: u32 = 0;
+
+static NEXT_BUFFER: AtomicU64 = AtomicU64::new(0);
+
+/// Pixels in shared memory, ready to be wrapped in a `wl_buffer`.
+///
+/// The memory is an unlinked file in `$XDG_RUNTIME_DIR` (or `/dev/shm`)
+/// holding premultiplied ARGB8888 pixels without row padding.
+#[derive(Debug)]
+pub struct ShmBuffer {
+    id: u64,
+    file: File,
+    width: u32,
+    height: u32,
+}
+impl ShmBuffer {
+    pub fn new(width: u32, height: u32) -> io::Result<Self> {
+        let id = NEXT_BUFFER.fetch_add(1, Ordering::Relaxed);
+        let dir =
+            env::var_os("XDG_RUNTIME_DIR").map_or_else(|| PathBuf::from("/dev/shm"), PathBuf::from);
+        let path = dir.join(format!("imgui-waywin-{}-{id}", process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        fs::remove_file(&path)?;
+
+        let buffer = Self {
+            id,
+            file,
+            width,
+            height,
+        };
+        buffer.file.set_len(buffer.size() as u64)?;
+        Ok(buffer)
+    }
+
+    /// Identifies the buffer to [`ShmHost`] and [`ShmPresenter::release`].
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Bytes per row.
+    pub fn stride(&self) -> u32 {
+        self.width * 4
+    }
+
+    /// Size of the memory in bytes, for `wl_shm.create_pool`.
+    pub fn size(&self) -> usize {
+        self.stride() as usize * self.height as usize
+    }
+
+    /// Copies a canvas of the same size into the buffer.
+    pub fn write(&self, canvas: &Canvas) -> io::Result<()> {
+        if (canvas.width(), canvas.height()) != (self.width, self.height) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "canvas size does not match the buffer",
+            ));
+        }
+        // ARGB8888 is little-endian, so each pixel is stored as B, G, R, A.
+        let mut bytes = canvas.pixels().to_vec();
+        for pixel in bytes.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        self.file.write_all_at(&bytes, 0)
+    }
+}
+impl AsFd for ShmBuffer {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+/// Shows shm buffers on a window, implemented by the host on top of its
+/// `wl_shm` global.
+pub trait ShmHost<W: ?Sized = Window> {
+    /// Attaches `buffer` to the window's surface, damages and commits it.
+    ///
+    /// The host creates a `wl_shm_pool` and `wl_buffer` from the buffer's
+    /// file descriptor the first time it sees its [`ShmBuffer::id`], and
+    /// reports `wl_buffer.release` through [`ShmPresenter::release`].
+    fn attach(&mut self, window: &W, buffer: &ShmBuffer);
+    /// Destroys the `wl_buffer` of a buffer that is no longer used.
+    fn destroy(&mut self, _id: u64) {}
+}
+
+struct Slot {
+    buffer: ShmBuffer,
+    busy: bool,
+}
+
+/// Renders draw data with a [`SoftwareRenderer`] and presents it through
+/// shm buffers, for hosts without a GPU.
+///
+/// Buffers stay busy from being attached until the compositor releases
+/// them, so a new one is allocated whenever all of them are busy.
+pub struct ShmPresenter {
+    renderer: SoftwareRenderer,
+    canvas: Canvas,
+    clear_color: [u8; 4],
+    slots: Vec<Slot>,
+}
+impl ShmPresenter {
+    pub fn new(imgui: &mut Context) -> Self {
+        Self {
+            renderer: SoftwareRenderer::new(imgui),
+            canvas: Canvas::new(0, 0),
+            clear_color: [0, 0, 0, 255],
+            slots: Vec::new(),
+        }
+    }
+
+    pub fn renderer(&mut self) -> &mut SoftwareRenderer {
+        &mut self.renderer
+    }
+
+    /// The last rendered frame.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Background colour as straight-alpha RGBA, opaque black by default.
+    pub fn set_clear_color(&mut self, color: [u8; 4]) {
+        self.clear_color = premultiply(color);
+    }
+
+    /// Renders `draw_data` at the window's physical size and attaches it.
+    pub fn present<W: PlatformWindow + ?Sized>(
+        &mut self,
+        draw_data: &DrawData,
+        window: &W,
+        host: &mut impl ShmHost<W>,
+    ) -> io::Result<()> {
+        let [width, height] = window.physical_size().map(|size| size as u32);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.canvas.resize(width, height);
+        self.canvas.clear(self.clear_color);
+        self.renderer.render(draw_data, &mut self.canvas);
+
+        self.slots.retain(|slot| {
+            let keep = slot.busy || (slot.buffer.width, slot.buffer.height) == (width, height);
+            if !keep {
+                host.destroy(slot.buffer.id);
+            }
+            keep
+        });
+        let index = match self.slots.iter().position(|slot| {
+            !slot.busy && (slot.buffer.width, slot.buffer.height) == (width, height)
+        }) {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    buffer: ShmBuffer::new(width, height)?,
+                    busy: false,
+                });
+                self.slots.len() - 1
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        slot.buffer.write(&self.canvas)?;
+        slot.busy = true;
+        host.attach(window, &slot.buffer);
+        Ok(())
+    }
+
+    /// Marks a buffer as released by the compositor, so it can be reused.
+    pub fn release(&mut self, id: u64) {
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.buffer.id == id) {
+            slot.busy = false;
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use imgui::{
+    Context, DrawCmd, DrawCmdParams, DrawData, DrawVert, TextureId, Textures, internal::RawWrapper,
+};
+use std::array;
+
+/// An RGBA8 image that draw data is rendered into.
+///
+/// Pixels are stored row by row without padding. Colours are blended the way
+/// imgui's GPU backends blend them, so the colour channels end up
+/// premultiplied by alpha wherever the canvas was cleared to transparent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+impl Canvas {
+    /// A transparent canvas.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Wraps existing RGBA8 pixels, or returns `None` if there are not
+    /// exactly `width * height` of them.
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        self.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    /// Changes the size, clearing the canvas if it changed.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) != (self.width, self.height) {
+            *self = Self::new(width, height);
+        }
+    }
+
+    pub fn clear(&mut self, color: [u8; 4]) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    /// Blends a straight-alpha colour like `SRC_ALPHA, ONE_MINUS_SRC_ALPHA`
+    /// for colour and `ONE, ONE_MINUS_SRC_ALPHA` for alpha.
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 4]) {
+        let i = self.index(x, y);
+        let pixel = &mut self.pixels[i..i + 4];
+        let alpha = color[3];
+        for (dst, src) in pixel[..3].iter_mut().zip(color) {
+            *dst = to_u8(src * alpha + *dst as f32 / 255.0 * (1.0 - alpha));
+        }
+        pixel[3] = to_u8(alpha + pixel[3] as f32 / 255.0 * (1.0 - alpha));
+    }
+}
+
+/// An RGBA8 texture the software renderer can sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoftwareTexture {
+    pub width: u32,
+    pub height: u32,
+    /// Straight-alpha RGBA8 pixels, row by row.
+    pub data: Vec<u8>,
+}
+impl SoftwareTexture {
+    /// Samples with bilinear filtering and clamp-to-edge addressing, like
+    /// the samplers of imgui's GPU backends.
+    fn sample(&self, [u, v]: [f32; 2]) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [1.0; 4];
+        }
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let texel = |x: f32, y: f32| {
+            let x = (x.max(0.0) as u32).min(self.width - 1) as usize;
+            let y = (y.max(0.0) as u32).min(self.height - 1) as usize;
+            let i = (y * self.width as usize + x) * 4;
+            array::from_fn(|c| self.data[i + c] as f32 / 255.0)
+        };
+        let top = lerp(texel(x0, y0), texel(x0 + 1.0, y0), fx);
+        let bottom = lerp(texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0), fx);
+        lerp(top, bottom, fy)
+    }
+}
+
+/// Renders imgui draw data on the CPU.
+///
+/// Triangles are rasterised with the top-left fill rule, clipped to their
+/// command's clip rectangle, and shaded with interpolated vertex colours
+/// multiplied by a bilinear texture sample. This is enough to reproduce
+/// what imgui's GPU backends draw without needing a GPU.
+pub struct SoftwareRenderer {
+    textures: Textures<SoftwareTexture>,
+    font_texture: TextureId,
+}
+impl SoftwareRenderer {
+    /// Creates a renderer and uploads imgui's font atlas.
+    pub fn new(imgui: &mut Context) -> Self {
+        let mut textures = Textures::new();
+        let font_texture = textures.insert(SoftwareTexture {
+            width: 0,
+            height: 0,
+            data: Vec::new(),
+        });
+        let mut renderer = Self {
+            textures,
+            font_texture,
+        };
+        renderer.reload_font_texture(imgui);
+        renderer
+    }
+
+    /// Uploads the font atlas again, e.g. after
+    /// [`WaywinPlatform::fonts_rebuilt`](crate::WaywinPlatform::fonts_rebuilt).
+    pub fn reload_font_texture(&mut self, imgui: &mut Context) {
+        let fonts = imgui.fonts();
+        let atlas = fonts.build_rgba32_texture();
+        let texture = SoftwareTexture {
+            width: atlas.width,
+            height: atlas.height,
+            data: atlas.data.to_vec(),
+        };
+        self.textures.replace(self.font_texture, texture);
+        fonts.tex_id = self.font_texture;
+    }
+
+    /// Textures that can be referenced by `Image` widgets.
+    pub fn textures(&mut self) -> &mut Textures<SoftwareTexture> {
+        &mut self.textures
+    }
+
+    /// Draws `draw_data` on top of the canvas.
+    ///
+    /// The canvas is treated as the framebuffer, so it should be
+    /// `display_size * framebuffer_scale` pixels large. Commands using
+    /// unknown textures are drawn untextured.
+    pub fn render(&self, draw_data: &DrawData, canvas: &mut Canvas) {
+        let origin = draw_data.display_pos;
+        let scale = draw_data.framebuffer_scale;
+        let to_canvas = |[x, y]: [f32; 2]| [(x - origin[0]) * scale[0], (y - origin[1]) * scale[1]];
+
+        for draw_list in draw_data.draw_lists() {
+            let vertices = draw_list.vtx_buffer();
+            let indices = draw_list.idx_buffer();
+            for command in draw_list.commands() {
+                match command {
+                    DrawCmd::Elements {
+                        count,
+                        cmd_params:
+                            DrawCmdParams {
+                                clip_rect,
+                                texture_id,
+                                vtx_offset,
+                                idx_offset,
+                            },
+                    } => {
+                        let [x0, y0] = to_canvas([clip_rect[0], clip_rect[1]]);
+                        let [x1, y1] = to_canvas([clip_rect[2], clip_rect[3]]);
+                        let clip = [x0, y0, x1, y1];
+                        let texture = self.textures.get(texture_id);
+                        for triangle in indices[idx_offset..idx_offset + count].chunks_exact(3) {
+                            let triangle = [0, 1, 2].map(|i| {
+                                let vertex = vertices[vtx_offset + triangle[i] as usize];
+                                DrawVert {
+                                    pos: to_canvas(vertex.pos),
+                                    ..vertex
+                                }
+                            });
+                            rasterize(canvas, clip, texture, triangle);
+                        }
+                    }
+                    // also what the `ImDrawCallback_ResetRenderState` sentinel
+                    // arrives as, so it never reaches the arm below
+                    DrawCmd::ResetRenderState => {}
+                    // SAFETY: imgui-rs only yields real user callbacks here,
+                    // with the draw list and command they were recorded in
+                    DrawCmd::RawCallback { callback, raw_cmd } => unsafe {
+                        callback(draw_list.raw(), raw_cmd)
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn rasterize(
+    canvas: &mut Canvas,
+    clip: [f32; 4],
+    texture: Option<&SoftwareTexture>,
+    [a, mut b, mut c]: [DrawVert; 3],
+) {
+    let mut area = edge(a.pos, b.pos, c.pos);
+    if area == 0.0 {
+        return;
+    }
+    // imgui emits both windings; flip so the edge functions are positive
+    // inside.
+    if area < 0.0 {
+        std::mem::swap(&mut b, &mut c);
+        area = -area;
+    }
+
+    // Pixels are covered when their centre is inside both the clip
+    // rectangle and the triangle's bounding box.
+    let span = |min: f32, max: f32, limit: u32| {
+        let start = (min - 0.5).ceil().max(0.0) as u32;
+        let end = ((max - 0.5).ceil().max(0.0) as u32).min(limit);
+        start..end
+    };
+    let xs = [a.pos[0], b.pos[0], c.pos[0]];
+    let ys = [a.pos[1], b.pos[1], c.pos[1]];
+    let xs = span(
+        clip[0].max(xs.into_iter().fold(f32::INFINITY, f32::min)),
+        clip[2].min(xs.into_iter().fold(f32::NEG_INFINITY, f32::max)),
+        canvas.width,
+    );
+    let ys = span(
+        clip[1].max(ys.into_iter().fold(f32::INFINITY, f32::min)),
+        clip[3].min(ys.into_iter().fold(f32::NEG_INFINITY, f32::max)),
+        canvas.height,
+    );
+
+    let edges = [(b.pos, c.pos), (c.pos, a.pos), (a.pos, b.pos)];
+    let colors = [a.col, b.col, c.col].map(|col| col.map(|c| c as f32 / 255.0));
+    for y in ys {
+        for x in xs.clone() {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let weights = edges.map(|(from, to)| edge(from, to, p));
+            let covered = weights
+                .iter()
+                .zip(edges)
+                .all(|(&w, (from, to))| w > 0.0 || (w == 0.0 && is_top_left(from, to)));
+            if !covered {
+                continue;
+            }
+            let weights = weights.map(|w| w / area);
+            let interpolate = |values: [[f32; 4]; 3]| {
+                array::from_fn(|c| (0..3).map(|v| values[v][c] * weights[v]).sum::<f32>())
+            };
+            let mut color: [f32; 4] = interpolate(colors);
+            if let Some(texture) = texture {
+                let [u, v, ..] = interpolate([a.uv, b.uv, c.uv].map(|[u, v]| [u, v, 0.0, 0.0]));
+                let texel = texture.sample([u, v]);
+                color = array::from_fn(|c| color[c] * texel[c]);
+            }
+            canvas.blend(x, y, color.map(|c| c.clamp(0.0, 1.0)));
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `from`, `to`, `p`; positive when
+/// `p` is to the right of the edge in y-down coordinates.
+fn edge(from: [f32; 2], to: [f32; 2], p: [f32; 2]) -> f32 {
+    (to[0] - from[0]) * (p[1] - from[1]) - (to[1] - from[1]) * (p[0] - from[0])
+}
+
+/// Whether an edge of a positively wound triangle is a top or a left edge,
+/// which own the pixels whose centres lie exactly on them.
+fn is_top_left(from: [f32; 2], to: [f32; 2]) -> bool {
+    let dx = to[0] - from[0];
+    let dy = to[1] - from[1];
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    array::from_fn(|c| a[c] + (b[c] - a[c]) * t)
+}
+
+/// Converts a straight-alpha colour to the canvas' premultiplied form.
+pub(crate) fn premultiply(color: [u8; 4]) -> [u8; 4] {
+    let alpha = color[3] as u16;
+    let scale = |c: u8| ((c as u16 * alpha + 127) / 255) as u8;
+    [scale(color[0]), scale(color[1]), scale(color[2]), color[3]]
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
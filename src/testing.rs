@@ -0,0 +1,427 @@
+use crate::{
+    Canvas, EventResponse, HeadlessWindow, PlatformWindow, SoftwareRenderer, WaywinPlatform,
+    software::premultiply,
+};
+use imgui::{Context, Io, Ui};
+use std::{
+    env,
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+use waywin::event::WindowEvent;
+
+/// Environment variable that makes [`Snapshots`] accept rendered images as
+/// the new baselines when set to anything but `0`.
+pub const BLESS_VAR: &str = "IMGUI_WAYWIN_BLESS";
+
+/// imgui allows only one active context at a time, so testers take turns.
+static CONTEXT: Mutex<()> = Mutex::new(());
+
+/// Runs imgui frames on a [`HeadlessWindow`] and renders them with the
+/// [`SoftwareRenderer`].
+///
+/// Frames are 1/60 s apart regardless of how long they take, and imgui's
+/// settings file and input trickling are disabled, so the same input always
+/// produces the same pixels. Testers hold a process-wide lock while they
+/// exist, which serialises tests that use them; tests must not create other
+/// imgui contexts meanwhile.
+pub struct UiTester {
+    imgui: Context,
+    platform: WaywinPlatform,
+    renderer: SoftwareRenderer,
+    window: HeadlessWindow,
+    clear_color: [u8; 4],
+    _guard: MutexGuard<'static, ()>,
+}
+impl UiTester {
+    pub fn new(logical_size: [f32; 2], scale: f32) -> Self {
+        let guard = CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+        let mut imgui = Context::create();
+        imgui.set_ini_filename(None);
+        imgui.set_log_filename(None);
+        imgui.io_mut().config_input_trickle_event_queue = false;
+
+        let window = HeadlessWindow::new(logical_size, scale);
+        let mut platform = WaywinPlatform::new(&mut imgui, &window);
+        let mut now = Duration::ZERO;
+        platform.set_time_source(move || {
+            now += Duration::from_secs(1) / 60;
+            now
+        });
+        let renderer = SoftwareRenderer::new(&mut imgui);
+        Self {
+            imgui,
+            platform,
+            renderer,
+            window,
+            clear_color: [0, 0, 0, 255],
+            _guard: guard,
+        }
+    }
+
+    pub fn imgui(&mut self) -> &mut Context {
+        &mut self.imgui
+    }
+
+    pub fn io(&self) -> &Io {
+        self.imgui.io()
+    }
+
+    pub fn platform(&mut self) -> &mut WaywinPlatform {
+        &mut self.platform
+    }
+
+    pub fn renderer(&mut self) -> &mut SoftwareRenderer {
+        &mut self.renderer
+    }
+
+    pub fn window(&self) -> &HeadlessWindow {
+        &self.window
+    }
+
+    /// Background colour as straight-alpha RGBA, opaque black by default.
+    pub fn set_clear_color(&mut self, color: [u8; 4]) {
+        self.clear_color = premultiply(color);
+    }
+
+    /// Delivers an event as if the window received it.
+    pub fn send(&mut self, event: WindowEvent) -> EventResponse {
+        self.platform
+            .handle_window_event(&mut self.imgui, &self.window, event)
+    }
+
+    /// Calls one of the platform's other handlers, such as
+    /// [`handle_scroll`](WaywinPlatform::handle_scroll), with the context.
+    pub fn with_platform<R>(
+        &mut self,
+        handle: impl FnOnce(&mut WaywinPlatform, &mut Context) -> R,
+    ) -> R {
+        handle(&mut self.platform, &mut self.imgui)
+    }
+
+    /// Changes the window's size and scale and reports both changes.
+    pub fn resize(&mut self, logical_size: [f32; 2], scale: f32) {
+        self.window = HeadlessWindow::new(logical_size, scale);
+        self.send(WindowEvent::Resized);
+        self.send(WindowEvent::NewScaleFactor);
+    }
+
+    /// Runs a frame that builds the UI with `build` and renders it.
+    pub fn frame(&mut self, build: impl FnOnce(&Ui)) -> Canvas {
+        self.platform.prepare_frame(&mut self.imgui, &self.window);
+        if self.platform.fonts_rebuilt() {
+            self.renderer.reload_font_texture(&mut self.imgui);
+        }
+        let ui = self.imgui.new_frame();
+        build(ui);
+        self.platform.prepare_render(ui, &self.window);
+        let draw_data = self.imgui.render();
+
+        let [width, height] = self.window.physical_size().map(|size| size as u32);
+        let mut canvas = Canvas::new(width, height);
+        canvas.clear(self.clear_color);
+        self.renderer.render(draw_data, &mut canvas);
+        canvas
+    }
+
+    /// Runs a frame like [`frame`](Self::frame) without rendering it, for
+    /// tests that only look at imgui's state.
+    pub fn step(&mut self, build: impl FnOnce(&Ui)) {
+        self.platform.prepare_frame(&mut self.imgui, &self.window);
+        if self.platform.fonts_rebuilt() {
+            self.renderer.reload_font_texture(&mut self.imgui);
+        }
+        let ui = self.imgui.new_frame();
+        build(ui);
+        self.platform.prepare_render(ui, &self.window);
+        self.imgui.render();
+    }
+
+    /// Runs `count` frames and returns the last one.
+    ///
+    /// Windows take a couple of frames to settle their size and position
+    /// after they first appear.
+    pub fn frames(&mut self, count: usize, mut build: impl FnMut(&Ui)) -> Canvas {
+        for _ in 1..count {
+            self.frame(&mut build);
+        }
+        self.frame(build)
+    }
+}
+
+/// How much a rendering may differ from its baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerance {
+    /// Largest difference in any channel for a pixel to count as equal.
+    pub channel: u8,
+    /// Number of pixels that may differ by more than `channel`.
+    pub pixels: usize,
+}
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0,
+        }
+    }
+}
+
+/// Compares renderings against PNG baselines in a directory.
+///
+/// The baseline of a snapshot `name` is `<dir>/<name>.png`. When a check
+/// fails, the rendering is written next to it as `<name>.actual.png`, along
+/// with `<name>.diff.png` marking differing pixels in red over a faded copy
+/// of the baseline. Both are removed again once the check passes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshots {
+    pub dir: PathBuf,
+    pub tolerance: Tolerance,
+    /// Write renderings that do not match as the new baselines instead of
+    /// failing. Defaults to whether [`BLESS_VAR`] is set.
+    pub bless: bool,
+}
+impl Snapshots {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            tolerance: Tolerance::default(),
+            bless: env::var_os(BLESS_VAR).is_some_and(|value| !value.is_empty() && value != "0"),
+        }
+    }
+
+    /// Compares `canvas` with the baseline called `name`.
+    pub fn check(&self, name: &str, canvas: &Canvas) -> Result<(), SnapshotError> {
+        let baseline = self.dir.join(format!("{name}.png"));
+        let actual = self.dir.join(format!("{name}.actual.png"));
+        let diff = self.dir.join(format!("{name}.diff.png"));
+
+        let expected = match read_png(&baseline) {
+            Ok(expected) => Some(expected),
+            Err(SnapshotError::Io(err)) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        let result = match expected {
+            None => Err(SnapshotError::Missing {
+                baseline: baseline.clone(),
+                actual: actual.clone(),
+            }),
+            Some(expected)
+                if (expected.width(), expected.height()) != (canvas.width(), canvas.height()) =>
+            {
+                Err(SnapshotError::SizeMismatch {
+                    expected: [expected.width(), expected.height()],
+                    actual: [canvas.width(), canvas.height()],
+                    actual_path: actual.clone(),
+                })
+            }
+            Some(expected) => {
+                let comparison = compare(&expected, canvas, self.tolerance.channel);
+                if comparison.differing <= self.tolerance.pixels {
+                    Ok(())
+                } else {
+                    if !self.bless {
+                        write_png(&diff, &comparison.diff)?;
+                    }
+                    Err(SnapshotError::Mismatch {
+                        differing: comparison.differing,
+                        max_difference: comparison.max_difference,
+                        actual: actual.clone(),
+                        diff: diff.clone(),
+                    })
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => {}
+            Err(_) if self.bless => write_png(&baseline, canvas)?,
+            Err(err) => {
+                write_png(&actual, canvas)?;
+                return Err(err);
+            }
+        }
+        for stale in [actual, diff] {
+            match fs::remove_file(stale) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`check`](Self::check), but panics if the rendering does not
+    /// match.
+    #[track_caller]
+    pub fn assert(&self, name: &str, canvas: &Canvas) {
+        if let Err(err) = self.check(name, canvas) {
+            panic!("snapshot `{name}`: {err}");
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// There is no baseline yet. The rendering was written to `actual`.
+    Missing {
+        baseline: PathBuf,
+        actual: PathBuf,
+    },
+    /// The rendering has a different size than the baseline.
+    SizeMismatch {
+        expected: [u32; 2],
+        actual: [u32; 2],
+        actual_path: PathBuf,
+    },
+    /// More pixels differ than the tolerance allows.
+    Mismatch {
+        differing: usize,
+        max_difference: u8,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+    Io(io::Error),
+    Decode(png::DecodingError),
+}
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { baseline, actual } => write!(
+                f,
+                "no baseline at {}, rendering written to {}; set {BLESS_VAR}=1 to accept it",
+                baseline.display(),
+                actual.display(),
+            ),
+            Self::SizeMismatch {
+                expected,
+                actual,
+                actual_path,
+            } => write!(
+                f,
+                "expected {}x{} pixels, rendered {}x{} to {}; set {BLESS_VAR}=1 to accept it",
+                expected[0],
+                expected[1],
+                actual[0],
+                actual[1],
+                actual_path.display(),
+            ),
+            Self::Mismatch {
+                differing,
+                max_difference,
+                actual,
+                diff,
+            } => write!(
+                f,
+                "{differing} pixels differ by up to {max_difference}, rendering written to {} \
+                 and differences to {}; set {BLESS_VAR}=1 to accept it",
+                actual.display(),
+                diff.display(),
+            ),
+            Self::Io(err) => err.fmt(f),
+            Self::Decode(err) => err.fmt(f),
+        }
+    }
+}
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+impl From<png::DecodingError> for SnapshotError {
+    fn from(err: png::DecodingError) -> Self {
+        Self::Decode(err)
+    }
+}
+
+/// Reads a PNG as 8-bit RGBA, so baselines survive PNG optimisers that
+/// change their colour type or bit depth.
+pub fn read_png(path: impl AsRef<Path>) -> Result<Canvas, SnapshotError> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    data.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => data,
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => unreachable!("palettes are expanded to RGB(A)"),
+    };
+    Ok(Canvas::from_pixels(info.width, info.height, pixels).expect("decoded a whole image"))
+}
+
+/// Writes a canvas as an 8-bit RGBA PNG, creating missing directories.
+pub fn write_png(path: impl AsRef<Path>, canvas: &Canvas) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, canvas.width(), canvas.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(canvas.pixels())?;
+    writer.finish()?;
+    Ok(())
+}
+
+struct Comparison {
+    differing: usize,
+    max_difference: u8,
+    diff: Canvas,
+}
+
+fn compare(expected: &Canvas, actual: &Canvas, tolerance: u8) -> Comparison {
+    let mut diff = Canvas::new(expected.width(), expected.height());
+    let mut differing = 0;
+    let mut max_difference = 0;
+    let pixels = expected
+        .pixels()
+        .chunks_exact(4)
+        .zip(actual.pixels().chunks_exact(4));
+    for ((expected, actual), out) in pixels.zip(diff.pixels_mut().chunks_exact_mut(4)) {
+        let difference = expected
+            .iter()
+            .zip(actual)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            differing += 1;
+            out.copy_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let luma = (expected[0] as u32 * 3 + expected[1] as u32 * 6 + expected[2] as u32) / 10;
+            let faded = (luma / 4 + 48) as u8;
+            out.copy_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+    Comparison {
+        differing,
+        max_difference,
+        diff,
+    }
+}
@@ -0,0 +1,45 @@
+use crate::Modifiers;
+
+/// Which text from key and input method events reaches imgui.
+///
+/// Text is filtered one code point at a time, so multi-codepoint sequences
+/// such as emoji with zero-width joiners or variation selectors pass through
+/// intact; those are format characters, not control characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextFilter {
+    /// Drops control characters, e.g. the `U+0001` produced by Ctrl+A.
+    ///
+    /// Enter, Tab and Backspace still work, as imgui reads them from key
+    /// events.
+    pub drop_control: bool,
+    /// Drops the text of key events while any of these modifiers is active,
+    /// as the key press is a shortcut rather than typing.
+    ///
+    /// Input method commits are never suppressed.
+    pub suppress_with: Modifiers,
+}
+impl Default for TextFilter {
+    /// Drops control characters and text typed with Ctrl or Super held.
+    fn default() -> Self {
+        Self {
+            drop_control: true,
+            suppress_with: Modifiers {
+                ctrl: true,
+                logo: true,
+                ..Modifiers::default()
+            },
+        }
+    }
+}
+impl TextFilter {
+    pub(crate) fn filter<'t>(
+        &self,
+        text: &'t str,
+        modifiers: Modifiers,
+    ) -> impl Iterator<Item = char> + 't {
+        let suppressed = self.suppress_with.intersects(modifiers);
+        let drop_control = self.drop_control;
+        text.chars()
+            .filter(move |char| !(suppressed || drop_control && char.is_control()))
+    }
+}
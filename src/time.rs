@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+/// A monotonic clock used to measure the time between frames.
+///
+/// Any `FnMut() -> Duration` closure is a time source, which makes it easy to
+/// drive the platform from a fixed timeline in tests and replays.
+pub trait TimeSource: 'static {
+    /// Time elapsed since an arbitrary, fixed origin.
+    fn now(&mut self) -> Duration;
+}
+
+impl<F: FnMut() -> Duration + 'static> TimeSource for F {
+    fn now(&mut self) -> Duration {
+        self()
+    }
+}
+
+/// The default [`TimeSource`], backed by [`Instant`].
+pub struct SystemClock {
+    origin: Instant,
+}
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl TimeSource for SystemClock {
+    fn now(&mut self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+pub(crate) struct FrameClock {
+    source: Box<dyn TimeSource>,
+    last: Option<Duration>,
+    max_delta: Duration,
+}
+impl FrameClock {
+    /// Delta reported for the first frame and after the time source changes.
+    pub const FIRST_FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+    /// Gaps longer than this (suspend, modal stalls) are clamped.
+    pub const DEFAULT_MAX_DELTA: Duration = Duration::from_millis(250);
+
+    pub fn new() -> Self {
+        Self {
+            source: Box::new(SystemClock::new()),
+            last: None,
+            max_delta: Self::DEFAULT_MAX_DELTA,
+        }
+    }
+
+    pub fn set_source(&mut self, source: Box<dyn TimeSource>) {
+        self.source = source;
+        self.last = None;
+    }
+
+    pub fn set_max_delta(&mut self, max_delta: Duration) {
+        self.max_delta = max_delta;
+    }
+
+    /// The current time of the source, without advancing the clock.
+    pub fn now(&mut self) -> Duration {
+        self.source.now()
+    }
+
+    /// Advances the clock and returns the clamped time since the last tick.
+    pub fn tick(&mut self) -> Duration {
+        let now = self.source.now();
+        let delta = match self.last.replace(now) {
+            // a source that goes backwards is treated like a zero-length frame
+            Some(last) => now.saturating_sub(last),
+            None => Self::FIRST_FRAME,
+        };
+        delta.min(self.max_delta)
+    }
+}
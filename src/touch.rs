@@ -0,0 +1,168 @@
+use crate::{
+    EventResponse,
+    held::HeldInputs,
+    record::{Input, InputEvent},
+};
+use imgui::{MouseButton, MouseSource};
+
+/// Touch events in logical window coordinates, as delivered by `wl_touch`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchEvent {
+    Down {
+        id: i32,
+        x: f64,
+        y: f64,
+    },
+    Motion {
+        id: i32,
+        x: f64,
+        y: f64,
+    },
+    Up {
+        id: i32,
+    },
+    /// The compositor took over the touch sequence, e.g. for a gesture.
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchSettings {
+    /// Turns two-finger drags into scroll wheel events.
+    pub two_finger_scroll: bool,
+    /// Finger travel, in logical pixels, that scrolls by one wheel step.
+    pub scroll_step: f32,
+}
+impl Default for TouchSettings {
+    fn default() -> Self {
+        Self {
+            two_finger_scroll: false,
+            scroll_step: 26.0,
+        }
+    }
+}
+
+/// Drives imgui's mouse with the first finger that touches the window.
+/// Further fingers are ignored, unless they start a two-finger scroll.
+///
+/// With two-finger scrolling enabled, the first finger's press is held back
+/// until it moves or lifts, so a second finger landing shortly after it can
+/// still turn the touch into a scroll without clicking.
+pub(crate) struct TouchState {
+    pub settings: TouchSettings,
+    touches: Vec<(i32, [f64; 2])>,
+    primary: Option<i32>,
+    /// Whether imgui has seen the primary finger's press.
+    pressed: bool,
+    /// Position of the two-finger centroid while scrolling. Stays set until
+    /// every finger is lifted so the remaining one does not click.
+    scroll_anchor: Option<[f64; 2]>,
+}
+impl TouchState {
+    pub fn new() -> Self {
+        Self {
+            settings: TouchSettings::default(),
+            touches: Vec::new(),
+            primary: None,
+            pressed: false,
+            scroll_anchor: None,
+        }
+    }
+
+    pub fn handle_event(
+        &mut self,
+        input: &mut Input,
+        held: &mut HeldInputs,
+        event: TouchEvent,
+    ) -> EventResponse {
+        let response = EventResponse::pointer(input.io);
+        input.send(InputEvent::MouseSource(MouseSource::TouchScreen));
+        match event {
+            TouchEvent::Down { id, x, y } => {
+                self.touches.push((id, [x, y]));
+                if self.scroll_anchor.is_some() {
+                    return response;
+                }
+                if self.primary.is_none() {
+                    self.primary = Some(id);
+                    self.pressed = false;
+                    input.send(InputEvent::MousePos([x as f32, y as f32]));
+                    if !self.settings.two_finger_scroll {
+                        self.press(input, held);
+                    }
+                } else if self.settings.two_finger_scroll && self.touches.len() == 2 {
+                    if self.pressed {
+                        // too late to hold the press back; release it away
+                        // from any widget so nothing is clicked
+                        input.send(InputEvent::MousePos([-f32::MAX, -f32::MAX]));
+                        held.add_mouse_button_event(input, MouseButton::Left, false);
+                        self.pressed = false;
+                    }
+                    self.scroll_anchor = Some(self.centroid());
+                }
+            }
+            TouchEvent::Motion { id, x, y } => {
+                if let Some((_, position)) = self.touches.iter_mut().find(|(touch, _)| *touch == id)
+                {
+                    *position = [x, y];
+                }
+                if let Some(anchor) = self.scroll_anchor {
+                    if self.touches.len() >= 2 {
+                        let centroid = self.centroid();
+                        let step = self.settings.scroll_step as f64;
+                        input.send(InputEvent::MouseWheel([
+                            ((centroid[0] - anchor[0]) / step) as f32,
+                            ((centroid[1] - anchor[1]) / step) as f32,
+                        ]));
+                        self.scroll_anchor = Some(centroid);
+                    }
+                } else if self.primary == Some(id) {
+                    self.press(input, held);
+                    input.send(InputEvent::MousePos([x as f32, y as f32]));
+                }
+            }
+            TouchEvent::Up { id } => {
+                self.touches.retain(|(touch, _)| *touch != id);
+                if self.scroll_anchor.is_none() && self.primary == Some(id) {
+                    // a tap that never moved is pressed and released at once
+                    self.press(input, held);
+                    held.add_mouse_button_event(input, MouseButton::Left, false);
+                }
+                if self.primary == Some(id) {
+                    self.primary = None;
+                    self.pressed = false;
+                }
+                if self.touches.is_empty() {
+                    self.primary = None;
+                    self.scroll_anchor = None;
+                }
+            }
+            TouchEvent::Cancel => {
+                if self.pressed {
+                    held.add_mouse_button_event(input, MouseButton::Left, false);
+                }
+                self.touches.clear();
+                self.primary = None;
+                self.pressed = false;
+                self.scroll_anchor = None;
+            }
+        }
+        response
+    }
+
+    /// Sends the primary finger's held-back press, if it is still pending.
+    fn press(&mut self, input: &mut Input, held: &mut HeldInputs) {
+        if !self.pressed {
+            self.pressed = true;
+            held.add_mouse_button_event(input, MouseButton::Left, true);
+        }
+    }
+
+    fn centroid(&self) -> [f64; 2] {
+        let count = self.touches.len().max(1) as f64;
+        let [x, y] = self
+            .touches
+            .iter()
+            .fold([0.0, 0.0], |[x, y], (_, [tx, ty])| [x + tx, y + ty]);
+        [x / count, y / count]
+    }
+}
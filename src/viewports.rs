@@ -0,0 +1,310 @@
+use crate::PlatformWindow;
+use imgui::{Context, Id, PlatformMonitor, PlatformViewportBackend, Viewport};
+use std::{any::Any, cell::RefCell, collections::HashMap, ffi::c_void, ptr, rc::Rc, sync::Arc};
+use waywin::event::WindowEvent;
+
+/// Where imgui wants a viewport window to be.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// Position on imgui's virtual desktop.
+    pub position: [f32; 2],
+    /// Position relative to the main window, for hosts that place viewport
+    /// windows as popups or subsurfaces of it.
+    pub offset: [f32; 2],
+}
+
+/// Window management for imgui's extra viewports, implemented by the host.
+///
+/// Wayland does not let clients place toplevel windows, so positioning is
+/// optional: viewport positions are then kept on a virtual desktop whose
+/// origin is the main window, which keeps pointer input consistent even when
+/// the compositor puts the window elsewhere.
+pub trait ViewportHost: 'static {
+    /// The host's windows, usually [`waywin::Window`]. Tests can use a
+    /// [`HeadlessWindow`](crate::HeadlessWindow) and feed its events to
+    /// [`WaywinPlatform::handle_viewport_event`](crate::WaywinPlatform::handle_viewport_event).
+    type Window: PlatformWindow + 'static;
+
+    /// Creates a hidden window for a new viewport.
+    ///
+    /// Returning `None` leaves the viewport without a window.
+    fn create_window(&mut self) -> Option<Arc<Self::Window>>;
+    /// Destroys a viewport window. Dropping it is enough by default.
+    fn destroy_window(&mut self, window: Arc<Self::Window>) {
+        drop(window);
+    }
+    fn show_window(&mut self, _window: &Self::Window) {}
+    /// Moves a window, returning `false` if this is not possible.
+    ///
+    /// The window then keeps its position on the virtual desktop, and the
+    /// host should show it as a popup or subsurface of the main window at
+    /// [`WaywinPlatform::viewport_placement`](crate::WaywinPlatform::viewport_placement).
+    fn set_window_position(&mut self, _window: &Self::Window, _placement: Placement) -> bool {
+        false
+    }
+    fn set_window_size(&mut self, _window: &Self::Window, _size: [f32; 2]) {}
+    fn focus_window(&mut self, _window: &Self::Window) {}
+    fn set_window_title(&mut self, _window: &Self::Window, _title: &str) {}
+    /// Whether a window is minimised. Wayland does not report this, so by
+    /// default windows never are.
+    fn is_minimized(&mut self, _window: &Self::Window) -> bool {
+        false
+    }
+    /// Monitors on the virtual desktop.
+    ///
+    /// By default a single monitor covering the main window is reported.
+    fn monitors(&mut self) -> Option<Vec<PlatformMonitor>> {
+        None
+    }
+}
+
+struct ViewportWindow<W> {
+    window: Arc<W>,
+    position: [f32; 2],
+    /// Where the window has to be placed relative to the main window,
+    /// because the host could not move it.
+    placement: Option<Placement>,
+    scale: f32,
+    focused: bool,
+}
+
+/// The viewport state the platform works with, whatever the host's window
+/// type.
+pub(crate) trait Viewports {
+    fn set_main_focus(&mut self, focused: bool);
+    /// The `Arc` holding the window of a viewport.
+    fn window(&self, id: Id) -> Option<&dyn Any>;
+    fn placement(&self, id: Id) -> Option<Placement>;
+    /// The viewport whose window matches `is_window`, which is passed the
+    /// host's window.
+    fn find(&self, is_window: &dyn Fn(&dyn Any) -> bool) -> Option<Id>;
+    /// Passes window state changes of a viewport window on to imgui through
+    /// the viewport's platform data, and returns the window's origin, or
+    /// `None` if there is no such viewport.
+    fn handle_window_event(
+        &mut self,
+        imgui: &mut Context,
+        id: Id,
+        event: &WindowEvent,
+    ) -> Option<[f32; 2]>;
+    /// Reports the monitors, by default one covering the main window.
+    fn update_monitors(&mut self, imgui: &mut Context, main_size: [f32; 2], main_scale: f32);
+}
+
+struct HostViewports<H: ViewportHost> {
+    host: H,
+    main_id: Id,
+    main_focused: bool,
+    windows: HashMap<Id, ViewportWindow<H::Window>>,
+}
+impl<H: ViewportHost> Viewports for HostViewports<H> {
+    fn set_main_focus(&mut self, focused: bool) {
+        self.main_focused = focused;
+    }
+
+    fn window(&self, id: Id) -> Option<&dyn Any> {
+        self.windows
+            .get(&id)
+            .map(|viewport| &viewport.window as &dyn Any)
+    }
+
+    fn placement(&self, id: Id) -> Option<Placement> {
+        self.windows.get(&id)?.placement
+    }
+
+    fn find(&self, is_window: &dyn Fn(&dyn Any) -> bool) -> Option<Id> {
+        self.windows
+            .iter()
+            .find(|(_, viewport)| is_window(&*viewport.window))
+            .map(|(id, _)| *id)
+    }
+
+    fn handle_window_event(
+        &mut self,
+        imgui: &mut Context,
+        id: Id,
+        event: &WindowEvent,
+    ) -> Option<[f32; 2]> {
+        let window = self.windows.get_mut(&id)?;
+        let viewport = imgui.viewport_by_id_mut(id)?;
+        // imgui reads requests in the next frame and clears them once it has
+        // updated the platform windows, so they are set as events arrive
+        match event {
+            WindowEvent::Resized => viewport.platform_request_resize = true,
+            WindowEvent::NewScaleFactor => {
+                window.scale = window.window.scale();
+                viewport.dpi_scale = window.scale;
+            }
+            WindowEvent::Close => viewport.platform_request_close = true,
+            WindowEvent::Focus(focused) => window.focused = *focused,
+            _ => {}
+        }
+        Some(window.position)
+    }
+
+    fn update_monitors(&mut self, imgui: &mut Context, main_size: [f32; 2], main_scale: f32) {
+        let monitors = self.host.monitors().unwrap_or_else(|| {
+            vec![PlatformMonitor {
+                main_pos: [0.0, 0.0],
+                main_size,
+                work_pos: [0.0, 0.0],
+                work_size: main_size,
+                dpi_scale: main_scale,
+            }]
+        });
+        imgui
+            .platform_io_mut()
+            .monitors
+            .replace_from_slice(&monitors);
+    }
+}
+
+pub(crate) fn install<H: ViewportHost>(imgui: &mut Context, host: H) -> Rc<RefCell<dyn Viewports>> {
+    let viewports = Rc::new(RefCell::new(HostViewports {
+        host,
+        main_id: imgui.main_viewport().id,
+        main_focused: true,
+        windows: HashMap::new(),
+    }));
+    imgui.set_platform_backend(ViewportBackend {
+        viewports: viewports.clone(),
+    });
+    // imgui checks that the platform claimed the main viewport
+    imgui.main_viewport_mut().platform_user_data = Rc::as_ptr(&viewports) as *mut c_void;
+    viewports
+}
+
+struct ViewportBackend<H: ViewportHost> {
+    viewports: Rc<RefCell<HostViewports<H>>>,
+}
+impl<H: ViewportHost> ViewportBackend<H> {
+    fn with<R>(
+        &mut self,
+        viewport: &Viewport,
+        f: impl FnOnce(&mut H, &mut ViewportWindow<H::Window>) -> R,
+    ) -> Option<R> {
+        let viewports = &mut *self.viewports.borrow_mut();
+        let window = viewports.windows.get_mut(&viewport.id)?;
+        Some(f(&mut viewports.host, window))
+    }
+
+    fn is_main(&self, viewport: &Viewport) -> bool {
+        viewport.id == self.viewports.borrow().main_id
+    }
+}
+impl<H: ViewportHost> PlatformViewportBackend for ViewportBackend<H> {
+    fn create_window(&mut self, viewport: &mut Viewport) {
+        let viewports = &mut *self.viewports.borrow_mut();
+        if let Some(window) = viewports.host.create_window() {
+            viewports.windows.insert(
+                viewport.id,
+                ViewportWindow {
+                    scale: window.scale(),
+                    window,
+                    position: viewport.pos,
+                    placement: None,
+                    focused: false,
+                },
+            );
+        }
+    }
+
+    fn destroy_window(&mut self, viewport: &mut Viewport) {
+        let viewports = &mut *self.viewports.borrow_mut();
+        if let Some(removed) = viewports.windows.remove(&viewport.id) {
+            viewports.host.destroy_window(removed.window);
+        }
+        // releases the main viewport, which imgui destroys with the context
+        viewport.platform_user_data = ptr::null_mut();
+    }
+
+    fn show_window(&mut self, viewport: &mut Viewport) {
+        self.with(viewport, |host, viewport| {
+            host.show_window(&viewport.window)
+        });
+    }
+
+    fn set_window_pos(&mut self, viewport: &mut Viewport, pos: [f32; 2]) {
+        self.with(viewport, |host, viewport| {
+            let placement = Placement {
+                position: pos,
+                // the main window sits at the origin of the virtual desktop
+                offset: pos,
+            };
+            viewport.position = pos;
+            let moved = host.set_window_position(&viewport.window, placement);
+            viewport.placement = (!moved).then_some(placement);
+        });
+    }
+
+    fn get_window_pos(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+        self.with(viewport, |_, viewport| viewport.position)
+            .unwrap_or([0.0, 0.0])
+    }
+
+    fn set_window_size(&mut self, viewport: &mut Viewport, size: [f32; 2]) {
+        self.with(viewport, |host, viewport| {
+            host.set_window_size(&viewport.window, size)
+        });
+    }
+
+    fn get_window_size(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+        let size = viewport.size;
+        self.with(viewport, |_, viewport| viewport.window.logical_size())
+            .unwrap_or(size)
+    }
+
+    fn set_window_focus(&mut self, viewport: &mut Viewport) {
+        self.with(viewport, |host, viewport| {
+            host.focus_window(&viewport.window)
+        });
+    }
+
+    fn get_window_focus(&mut self, viewport: &mut Viewport) -> bool {
+        if self.is_main(viewport) {
+            return self.viewports.borrow().main_focused;
+        }
+        self.with(viewport, |_, viewport| viewport.focused)
+            .unwrap_or(false)
+    }
+
+    fn get_window_minimized(&mut self, viewport: &mut Viewport) -> bool {
+        if self.is_main(viewport) {
+            // the main viewport's size is the main window's display size
+            return viewport.size[0] <= 0.0 || viewport.size[1] <= 0.0;
+        }
+        self.with(viewport, |host, viewport| {
+            host.is_minimized(&viewport.window)
+        })
+        .unwrap_or(false)
+    }
+
+    fn set_window_title(&mut self, viewport: &mut Viewport, title: &str) {
+        self.with(viewport, |host, viewport| {
+            host.set_window_title(&viewport.window, title)
+        });
+    }
+
+    fn set_window_alpha(&mut self, _viewport: &mut Viewport, _alpha: f32) {}
+
+    fn update_window(&mut self, viewport: &mut Viewport) {
+        // imgui resets the scale to its monitor's in every new frame
+        if let Some(scale) = self.with(viewport, |_, window| window.scale) {
+            viewport.dpi_scale = scale;
+        }
+    }
+
+    fn render_window(&mut self, _viewport: &mut Viewport) {}
+
+    fn swap_buffers(&mut self, _viewport: &mut Viewport) {}
+
+    fn create_vk_surface(
+        &mut self,
+        _viewport: &mut Viewport,
+        _instance: u64,
+        _out_surface: &mut u64,
+    ) -> i32 {
+        // the host creates surfaces for the windows it hands out
+        -1
+    }
+}
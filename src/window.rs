@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use waywin::Window;
+
+/// The window state the platform reads while translating events.
+///
+/// Implemented for [`waywin::Window`]. Tests and headless hosts can use
+/// [`HeadlessWindow`] or their own implementation to drive
+/// [`WaywinPlatform`](crate::WaywinPlatform) without a compositor.
+pub trait PlatformWindow {
+    /// Size in logical pixels, imgui's coordinate space.
+    fn logical_size(&self) -> [f32; 2];
+    /// Size of the framebuffer in physical pixels.
+    fn physical_size(&self) -> [f32; 2];
+    fn scale(&self) -> f32;
+}
+
+impl PlatformWindow for Window {
+    fn logical_size(&self) -> [f32; 2] {
+        let (w, h) = self.get_logical_size();
+        [w as f32, h as f32]
+    }
+
+    fn physical_size(&self) -> [f32; 2] {
+        let (w, h) = self.get_physical_size();
+        [w as f32, h as f32]
+    }
+
+    fn scale(&self) -> f32 {
+        self.get_scale() as f32
+    }
+}
+
+impl<W: PlatformWindow + ?Sized> PlatformWindow for Arc<W> {
+    fn logical_size(&self) -> [f32; 2] {
+        (**self).logical_size()
+    }
+
+    fn physical_size(&self) -> [f32; 2] {
+        (**self).physical_size()
+    }
+
+    fn scale(&self) -> f32 {
+        (**self).scale()
+    }
+}
+
+/// A window that only exists as a size and a scale factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadlessWindow {
+    pub logical_size: [f32; 2],
+    pub scale: f32,
+    /// Tells windows apart when several are driven together, `0` by default.
+    pub id: u64,
+}
+impl HeadlessWindow {
+    pub fn new(logical_size: [f32; 2], scale: f32) -> Self {
+        Self {
+            logical_size,
+            scale,
+            id: 0,
+        }
+    }
+}
+impl PlatformWindow for HeadlessWindow {
+    fn logical_size(&self) -> [f32; 2] {
+        self.logical_size
+    }
+
+    /// The logical size scaled and rounded, like a compositor would.
+    fn physical_size(&self) -> [f32; 2] {
+        let [w, h] = self.logical_size;
+        [(w * self.scale).round(), (h * self.scale).round()]
+    }
+
+    fn scale(&self) -> f32 {
+        self.scale
+    }
+}
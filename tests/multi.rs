@@ -0,0 +1,92 @@
+use imgui::Context;
+use imgui_waywin_support::{
+    EventCategory, EventResponse, HeadlessWindow, PlatformWindow, WaywinMultiPlatform,
+};
+use std::sync::{Mutex, MutexGuard};
+use waywin::event::WindowEvent;
+
+/// imgui allows only one active context at a time, so tests take turns.
+static CONTEXT: Mutex<()> = Mutex::new(());
+
+fn window(id: u64) -> HeadlessWindow {
+    HeadlessWindow {
+        id,
+        ..HeadlessWindow::new([320.0, 240.0], 1.0)
+    }
+}
+
+fn context() -> Context {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.fonts().build_rgba32_texture();
+    imgui
+}
+
+/// The lock on imgui, which has to outlive the contexts, and two windows
+/// with ids 1 and 2.
+fn two_windows() -> (MutexGuard<'static, ()>, WaywinMultiPlatform<HeadlessWindow>) {
+    let guard = CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+    let mut multi = WaywinMultiPlatform::new();
+    multi.add_window(window(1), context());
+    multi.add_window(window(2), context());
+    (guard, multi)
+}
+
+fn id(id: u64) -> impl Fn(&HeadlessWindow) -> bool {
+    move |window| window.id == id
+}
+
+/// Runs a frame in the window's context and returns the mouse position.
+fn mouse_pos(multi: &mut WaywinMultiPlatform<HeadlessWindow>, window: u64) -> [f32; 2] {
+    multi
+        .with_window(id(window), |imgui, platform, window| {
+            platform.prepare_frame(imgui, window);
+            let ui = imgui.new_frame();
+            let pos = ui.io().mouse_pos;
+            platform.prepare_render(ui, window);
+            imgui.render();
+            pos
+        })
+        .unwrap()
+}
+
+#[test]
+fn windows_are_added_and_removed() {
+    let (_guard, mut multi) = two_windows();
+    let ids: Vec<_> = multi.windows().map(|window| window.id).collect();
+    assert_eq!(ids, [1, 2]);
+
+    let imgui = multi.remove_window(id(1)).unwrap();
+    assert!(multi.remove_window(id(1)).is_none());
+    let ids: Vec<_> = multi.windows().map(|window| window.id).collect();
+    assert_eq!(ids, [2]);
+    assert!(multi.with_window(id(1), |_, _, _| ()).is_none());
+
+    // the returned context can be attached again
+    let imgui = imgui.activate().unwrap();
+    let wide = HeadlessWindow {
+        id: 1,
+        ..HeadlessWindow::new([640.0, 240.0], 2.0)
+    };
+    multi.add_window(wide, imgui);
+    let size = multi.with_window(id(1), |imgui, _, window| {
+        (imgui.io().display_size, window.physical_size())
+    });
+    assert_eq!(size, Some(([640.0, 240.0], [1280.0, 480.0])));
+}
+
+#[test]
+fn events_reach_the_window_they_belong_to() {
+    let (_guard, mut multi) = two_windows();
+    let response = multi.handle_window_event(id(2), WindowEvent::PointerMoved(10.0, 20.0));
+    assert_eq!(response, EventResponse::PassThrough(EventCategory::Pointer));
+    let response = multi.handle_window_event(id(3), WindowEvent::PointerMoved(30.0, 40.0));
+    assert_eq!(response, EventResponse::Ignored);
+
+    assert_eq!(mouse_pos(&mut multi, 2), [10.0, 20.0]);
+    assert_eq!(mouse_pos(&mut multi, 1), [-f32::MAX, -f32::MAX]);
+
+    multi.remove_window(id(2));
+    let response = multi.handle_window_event(id(2), WindowEvent::PointerMoved(50.0, 60.0));
+    assert_eq!(response, EventResponse::Ignored);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "testing")]
+
+use imgui::Condition;
+use imgui_waywin_support::{
+    Canvas,
+    testing::{SnapshotError, Snapshots, Tolerance, UiTester, read_png, write_png},
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+use waywin::event::WindowEvent;
+
+/// A fresh directory for the baselines of one test.
+fn snapshot_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("imgui-waywin-{}-{test}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn snapshots(dir: &Path, bless: bool) -> Snapshots {
+    Snapshots {
+        bless,
+        ..Snapshots::new(dir)
+    }
+}
+
+fn panel(tester: &mut UiTester, label: &str) -> Canvas {
+    tester.frames(3, |ui| {
+        ui.window("panel")
+            .position([8.0, 8.0], Condition::Always)
+            .size([180.0, 100.0], Condition::Always)
+            .build(|| {
+                ui.text("snapshot");
+                ui.button(label);
+            });
+    })
+}
+
+#[test]
+fn rendering_is_deterministic() {
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    let first = panel(&mut tester, "OK");
+    let second = panel(&mut tester, "OK");
+    assert_eq!((first.width(), first.height()), (200, 120));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn panel_matches_the_committed_baseline() {
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    let canvas = panel(&mut tester, "OK");
+    Snapshots::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots"))
+        .assert("panel", &canvas);
+}
+
+#[test]
+fn blessed_baselines_are_matched() {
+    let dir = snapshot_dir("bless");
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    let canvas = panel(&mut tester, "OK");
+
+    let missing = snapshots(&dir, false).check("panel", &canvas);
+    assert!(matches!(missing, Err(SnapshotError::Missing { .. })));
+    assert!(dir.join("panel.actual.png").exists());
+
+    snapshots(&dir, true).check("panel", &canvas).unwrap();
+    assert_eq!(read_png(dir.join("panel.png")).unwrap(), canvas);
+    snapshots(&dir, false).check("panel", &canvas).unwrap();
+    assert!(!dir.join("panel.actual.png").exists());
+}
+
+#[test]
+fn mismatches_produce_a_diff_image() {
+    let dir = snapshot_dir("mismatch");
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    write_png(dir.join("panel.png"), &panel(&mut tester, "OK")).unwrap();
+
+    let changed = panel(&mut tester, "Cancel");
+    let result = snapshots(&dir, false).check("panel", &changed);
+    let Err(SnapshotError::Mismatch { differing, .. }) = result else {
+        panic!("expected a mismatch, got {result:?}");
+    };
+    assert!(differing > 0);
+    let diff = read_png(dir.join("panel.diff.png")).unwrap();
+    assert!(
+        diff.pixels()
+            .chunks_exact(4)
+            .any(|pixel| pixel == [255, 0, 0, 255])
+    );
+    assert_eq!(read_png(dir.join("panel.actual.png")).unwrap(), changed);
+
+    let lenient = Snapshots {
+        tolerance: Tolerance {
+            pixels: differing,
+            ..Tolerance::default()
+        },
+        ..snapshots(&dir, false)
+    };
+    lenient.check("panel", &changed).unwrap();
+    assert!(!dir.join("panel.diff.png").exists());
+}
+
+#[test]
+fn small_differences_are_tolerated() {
+    let dir = snapshot_dir("tolerance");
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    let canvas = panel(&mut tester, "OK");
+    write_png(dir.join("panel.png"), &canvas).unwrap();
+
+    let mut nudged = canvas.clone();
+    for value in nudged.pixels_mut().iter_mut().step_by(4) {
+        *value = value.saturating_add(2);
+    }
+    snapshots(&dir, false).check("panel", &nudged).unwrap();
+}
+
+#[test]
+fn size_changes_are_reported() {
+    let dir = snapshot_dir("size");
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    write_png(dir.join("panel.png"), &panel(&mut tester, "OK")).unwrap();
+
+    tester.resize([200.0, 120.0], 2.0);
+    let result = snapshots(&dir, false).check("panel", &panel(&mut tester, "OK"));
+    assert!(matches!(
+        result,
+        Err(SnapshotError::SizeMismatch {
+            expected: [200, 120],
+            actual: [400, 240],
+            ..
+        })
+    ));
+}
+
+#[test]
+fn input_reaches_the_rendered_ui() {
+    let mut tester = UiTester::new([200.0, 120.0], 1.0);
+    let idle = panel(&mut tester, "OK");
+    // hover the button, which imgui highlights
+    tester.send(WindowEvent::PointerMoved(24.0, 60.0));
+    let hovered = panel(&mut tester, "OK");
+    assert_ne!(idle, hovered);
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "software")]
+
+use imgui::{Context, Ui};
+use imgui_waywin_support::{
+    Canvas, HeadlessWindow, PlatformWindow, ShmBuffer, SoftwareRenderer, WaywinPlatform,
+};
+use std::{
+    fs::File,
+    os::{fd::AsFd, unix::fs::FileExt},
+    sync::Mutex,
+};
+
+/// imgui allows a single context per process, so tests take turns.
+static CONTEXT: Mutex<()> = Mutex::new(());
+
+/// Runs a frame and renders it onto a transparent canvas.
+fn render(scale: f32, build: impl FnOnce(&Ui)) -> Canvas {
+    let _guard = CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    let window = HeadlessWindow::new([64.0, 48.0], scale);
+    let mut platform = WaywinPlatform::new(&mut imgui, &window);
+    let renderer = SoftwareRenderer::new(&mut imgui);
+
+    platform.prepare_frame(&mut imgui, &window);
+    let ui = imgui.new_frame();
+    build(ui);
+    platform.prepare_render(ui, &window);
+    let [width, height] = window.physical_size();
+    let mut canvas = Canvas::new(width as u32, height as u32);
+    renderer.render(imgui.render(), &mut canvas);
+    canvas
+}
+
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+fn filled_rect(ui: &Ui, min: [f32; 2], max: [f32; 2]) {
+    ui.get_foreground_draw_list()
+        .add_rect(min, max, RED)
+        .filled(true)
+        .build();
+}
+
+#[test]
+fn fills_rectangles_edge_to_edge() {
+    let canvas = render(1.0, |ui| filled_rect(ui, [10.0, 10.0], [20.0, 20.0]));
+    assert_eq!(canvas.pixel(10, 10), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(19, 19), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(9, 15), [0; 4]);
+    assert_eq!(canvas.pixel(20, 15), [0; 4]);
+    assert_eq!(canvas.pixel(15, 20), [0; 4]);
+}
+
+#[test]
+fn clips_to_the_clip_rectangle() {
+    let canvas = render(1.0, |ui| {
+        let draw_list = ui.get_foreground_draw_list();
+        draw_list.with_clip_rect([0.0, 0.0], [15.0, 48.0], || {
+            draw_list
+                .add_rect([10.0, 10.0], [20.0, 20.0], RED)
+                .filled(true)
+                .build();
+        });
+    });
+    assert_eq!(canvas.pixel(14, 15), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(15, 15), [0; 4]);
+}
+
+#[test]
+fn scales_to_the_framebuffer() {
+    let canvas = render(2.0, |ui| filled_rect(ui, [10.0, 10.0], [20.0, 20.0]));
+    assert_eq!((canvas.width(), canvas.height()), (128, 96));
+    assert_eq!(canvas.pixel(20, 20), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(39, 39), [255, 0, 0, 255]);
+    assert_eq!(canvas.pixel(40, 30), [0; 4]);
+}
+
+#[test]
+fn draws_text_from_the_font_atlas() {
+    let canvas = render(1.0, |ui| {
+        ui.get_foreground_draw_list()
+            .add_text([2.0, 2.0], [1.0; 4], "Hello");
+    });
+    let inked = canvas
+        .pixels()
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] > 0)
+        .count();
+    assert!(inked > 20, "only {inked} pixels drawn");
+}
+
+#[test]
+fn shm_buffers_hold_argb8888() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.clear([10, 20, 30, 255]);
+    let buffer = ShmBuffer::new(2, 1).unwrap();
+    buffer.write(&canvas).unwrap();
+    assert_eq!((buffer.stride(), buffer.size()), (8, 8));
+
+    let file = File::from(buffer.as_fd().try_clone_to_owned().unwrap());
+    let mut bytes = [0; 8];
+    file.read_exact_at(&mut bytes, 0).unwrap();
+    assert_eq!(bytes, [30, 20, 10, 255, 30, 20, 10, 255]);
+
+    assert!(buffer.write(&Canvas::new(1, 1)).is_err());
+}
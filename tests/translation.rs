@@ -0,0 +1,856 @@
+#![cfg(feature = "testing")]
+
+use imgui::{BackendFlags, FontConfig, FontSource, Key as ImguiKey, MouseButton, MouseSource};
+use imgui_waywin_support::{
+    ComposeTable, EventCategory, EventResponse, FontBuilder, ImeEvent, InputEvent, KeyMap,
+    ModifierState, Modifiers, PenEvent, PenTool, PointerEvent, Recording, Replay, ScalePolicy,
+    ScrollAxis, ScrollEvent, ScrollSettings, ScrollSource, TouchEvent, TouchSettings,
+    testing::UiTester,
+};
+use std::{
+    cell::{Cell, RefCell},
+    env, fs,
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+    process,
+    rc::Rc,
+    time::Duration,
+};
+use waywin::event::{Key, LogicalKey, PointerButton, ScrollDirection, WindowEvent};
+
+/// A [`UiTester`] on an 800x600 window with helpers for the input handlers
+/// that are not window events.
+struct Harness(UiTester);
+impl Harness {
+    fn new() -> Self {
+        Self(UiTester::new([800.0, 600.0], 1.0))
+    }
+
+    fn pointer(&mut self, event: PointerEvent) -> EventResponse {
+        self.with_platform(|platform, imgui| platform.handle_pointer(imgui, event))
+    }
+
+    fn scroll(&mut self, event: ScrollEvent) -> EventResponse {
+        self.with_platform(|platform, imgui| platform.handle_scroll(imgui, event))
+    }
+
+    fn pen(&mut self, event: PenEvent) -> EventResponse {
+        self.with_platform(|platform, imgui| platform.handle_pen(imgui, event))
+    }
+
+    fn touch(&mut self, event: TouchEvent) -> EventResponse {
+        self.with_platform(|platform, imgui| platform.handle_touch(imgui, event))
+    }
+
+    /// Runs an empty frame so imgui processes the queued input.
+    fn frame(&mut self) {
+        self.step(|_| {});
+    }
+
+    fn key_down(&mut self, key: ImguiKey) -> bool {
+        let mut down = false;
+        self.step(|ui| down = ui.is_key_down(key));
+        down
+    }
+
+    /// Wheel steps imgui applies in the next frame. imgui clears them when
+    /// the frame ends, so they are read while it runs.
+    fn wheel(&mut self) -> [f32; 2] {
+        let mut wheel = [0.0; 2];
+        self.step(|ui| wheel = [ui.io().mouse_wheel_h, ui.io().mouse_wheel]);
+        wheel
+    }
+
+    /// Runs a frame with a focused text field editing `buffer`.
+    fn text_field(&mut self, buffer: &mut String) {
+        self.step(|ui| {
+            ui.window("text").build(|| {
+                if ui.is_window_appearing() {
+                    ui.set_keyboard_focus_here();
+                }
+                ui.input_text("##text", buffer).build();
+            });
+        });
+    }
+}
+impl Deref for Harness {
+    type Target = UiTester;
+
+    fn deref(&self) -> &UiTester {
+        &self.0
+    }
+}
+impl DerefMut for Harness {
+    fn deref_mut(&mut self) -> &mut UiTester {
+        &mut self.0
+    }
+}
+
+/// A key event without modifiers, `scancode` being the evdev code.
+macro_rules! key {
+    ($down:expr, $scancode:expr, $key:expr, $text:expr) => {
+        WindowEvent::Key {
+            down: $down,
+            physical_key: ($scancode as u32).into(),
+            logical_key: $key,
+            text: $text.into(),
+            text_raw: $text.into(),
+            logical_key_unmodified: $key,
+        }
+    };
+}
+
+fn tab(down: bool) -> WindowEvent {
+    key!(down, 15, LogicalKey::Key(Key::Tab), "")
+}
+
+#[test]
+fn pointer_motion_and_buttons() {
+    let mut h = Harness::new();
+    let response = h.send(WindowEvent::PointerMoved(10.0, 20.0));
+    assert_eq!(response, EventResponse::PassThrough(EventCategory::Pointer));
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Left,
+    });
+    h.frame();
+    assert_eq!(h.io().mouse_pos, [10.0, 20.0]);
+    assert!(h.io().mouse_down[MouseButton::Left as usize]);
+
+    h.send(WindowEvent::PointerButton {
+        down: false,
+        button: PointerButton::Left,
+    });
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Back,
+    });
+    h.frame();
+    assert!(!h.io().mouse_down[MouseButton::Left as usize]);
+    assert!(h.io().mouse_down[MouseButton::Extra1 as usize]);
+}
+
+#[test]
+fn pointer_leave_hides_the_position_and_releases_buttons() {
+    let mut h = Harness::new();
+    h.send(WindowEvent::PointerMoved(10.0, 20.0));
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Left,
+    });
+    h.frame();
+
+    h.pointer(PointerEvent::Leave);
+    h.frame();
+    assert_eq!(h.io().mouse_pos, [-f32::MAX, -f32::MAX]);
+    assert!(!h.io().mouse_down[MouseButton::Left as usize]);
+
+    h.pointer(PointerEvent::Enter { position: None });
+    h.frame();
+    assert_eq!(h.io().mouse_pos, [10.0, 20.0]);
+
+    h.pointer(PointerEvent::Leave);
+    h.pointer(PointerEvent::Enter {
+        position: Some([3.0, 4.0]),
+    });
+    h.frame();
+    assert_eq!(h.io().mouse_pos, [3.0, 4.0]);
+}
+
+#[test]
+fn extra_buttons_are_bound_or_passed_to_the_callback() {
+    let mut h = Harness::new();
+    let pressed = Rc::new(RefCell::new(Vec::new()));
+    let log = pressed.clone();
+    h.platform()
+        .set_extra_button_callback(move |code, down| log.borrow_mut().push((code, down)));
+    h.platform().bind_mouse_button(0x117, MouseButton::Extra2);
+
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Unknown(0x117),
+    });
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Unknown(0x118),
+    });
+    h.frame();
+    assert!(h.io().mouse_down[MouseButton::Extra2 as usize]);
+    assert_eq!(*pressed.borrow(), [(0x118, true)]);
+}
+
+#[test]
+fn key_press_and_release() {
+    let mut h = Harness::new();
+    let response = h.send(tab(true));
+    assert_eq!(
+        response,
+        EventResponse::PassThrough(EventCategory::Keyboard)
+    );
+    h.frame();
+    assert!(h.key_down(ImguiKey::Tab));
+
+    h.send(tab(false));
+    h.frame();
+    assert!(!h.key_down(ImguiKey::Tab));
+}
+
+#[test]
+fn modifiers_follow_modifier_keys() {
+    let mut h = Harness::new();
+    h.send(key!(true, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.frame();
+    assert!(h.io().key_ctrl);
+    assert!(h.key_down(ImguiKey::LeftCtrl));
+
+    h.send(key!(false, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.frame();
+    assert!(!h.io().key_ctrl);
+}
+
+#[test]
+fn left_and_right_modifiers_are_independent() {
+    let mut h = Harness::new();
+    h.send(key!(true, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.send(key!(true, 97, LogicalKey::Key(Key::RCtrl), ""));
+    h.send(key!(false, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.frame();
+    assert!(h.io().key_ctrl);
+    assert!(!h.key_down(ImguiKey::LeftCtrl));
+    assert!(h.key_down(ImguiKey::RightCtrl));
+
+    h.send(key!(false, 97, LogicalKey::Key(Key::RCtrl), ""));
+    h.frame();
+    assert!(!h.io().key_ctrl);
+}
+
+#[test]
+fn compositor_modifier_state_is_authoritative() {
+    let mut h = Harness::new();
+    h.with_platform(|platform, imgui| {
+        platform.handle_modifiers(
+            imgui,
+            ModifierState {
+                depressed: Modifiers {
+                    shift: true,
+                    ..Default::default()
+                },
+                locked: Modifiers::from_xkb_mask(0b10010),
+                ..Default::default()
+            },
+        )
+    });
+    h.frame();
+    assert!(h.io().key_shift);
+    let modifiers = h.platform().modifiers();
+    assert!(modifiers.caps_lock && modifiers.num_lock);
+
+    // key events no longer change the modifiers
+    h.send(key!(false, 42, LogicalKey::Key(Key::LShift), ""));
+    h.frame();
+    assert!(h.io().key_shift);
+
+    h.with_platform(|platform, imgui| platform.handle_modifiers(imgui, ModifierState::default()));
+    h.frame();
+    assert!(!h.io().key_shift);
+    assert!(!h.platform().modifiers().caps_lock);
+}
+
+#[test]
+fn characters_map_to_letter_keys() {
+    let mut h = Harness::new();
+    h.send(key!(true, 30, LogicalKey::Character("a".into()), "a"));
+    h.frame();
+    assert!(h.key_down(ImguiKey::A));
+}
+
+#[test]
+fn text_filter_drops_control_characters_and_shortcuts() {
+    let mut h = Harness::new();
+    let mut buffer = String::new();
+    h.text_field(&mut buffer);
+    h.text_field(&mut buffer);
+
+    h.send(key!(true, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.send(key!(true, 30, LogicalKey::Character("a".into()), "\u{1}"));
+    h.send(key!(false, 30, LogicalKey::Character("a".into()), ""));
+    h.send(key!(false, 29, LogicalKey::Key(Key::LCtrl), ""));
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "");
+
+    let response = h.send(key!(true, 30, LogicalKey::Character("a".into()), "a\u{7f}"));
+    assert_eq!(response, EventResponse::Consumed(EventCategory::Text));
+    h.send(key!(false, 30, LogicalKey::Character("a".into()), ""));
+    h.text_field(&mut buffer);
+    // a zero-width joiner sequence stays in one piece
+    h.with_platform(|platform, imgui| {
+        platform.handle_ime(imgui, ImeEvent::Commit("\u{1f469}\u{200d}\u{1f4bb}".into()))
+    });
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "a\u{1f469}\u{200d}\u{1f4bb}");
+}
+
+#[test]
+fn dead_keys_commit_the_composed_character_once() {
+    let mut h = Harness::new();
+    let mut buffer = String::new();
+    h.text_field(&mut buffer);
+    h.text_field(&mut buffer);
+
+    h.with_platform(|platform, imgui| platform.handle_compose_key(imgui, "dead_acute"));
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "");
+    h.send(key!(true, 18, LogicalKey::Character("e".into()), "e"));
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "\u{e9}");
+
+    h.platform().set_compose_table(ComposeTable::parse(
+        "include \"%L\"\n<Multi_key> <o> <c> : \"\u{a9}\" copyright\n",
+    ));
+    h.with_platform(|platform, imgui| platform.handle_compose_key(imgui, "Multi_key"));
+    h.send(key!(true, 24, LogicalKey::Character("o".into()), "o"));
+    h.send(key!(true, 46, LogicalKey::Character("c".into()), "c"));
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "\u{e9}\u{a9}");
+}
+
+#[test]
+fn compose_files_follow_includes() {
+    let dir = env::temp_dir().join(format!("imgui-waywin-compose-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let base = dir.join("base");
+    fs::write(&base, "<Multi_key> <o> <c> : \"\u{a9}\"\n").unwrap();
+    let user = dir.join("user");
+    fs::write(
+        &user,
+        format!(
+            "include \"{}\"\ninclude \"%H/missing\"\n<Multi_key> <o> <r> : \"\u{ae}\"\n",
+            base.display()
+        ),
+    )
+    .unwrap();
+
+    let mut h = Harness::new();
+    h.platform()
+        .set_compose_table(ComposeTable::load(&user).unwrap());
+    let mut buffer = String::new();
+    h.text_field(&mut buffer);
+    h.text_field(&mut buffer);
+    for second in ["c", "r"] {
+        h.with_platform(|platform, imgui| platform.handle_compose_key(imgui, "Multi_key"));
+        h.send(key!(true, 24, LogicalKey::Character("o".into()), "o"));
+        h.send(key!(true, 46, LogicalKey::Character(second.into()), second));
+    }
+    h.text_field(&mut buffer);
+    assert_eq!(buffer, "\u{a9}\u{ae}");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn key_maps_reject_unknown_key_names() {
+    assert!("key NumpadEnter = KeypadEnter".parse::<KeyMap>().is_ok());
+    let err = "# bindings\nkey Tab = Tab\nkey Tabb = Tab"
+        .parse::<KeyMap>()
+        .unwrap_err();
+    assert_eq!(err.line, 3);
+}
+
+#[test]
+fn keypad_keys_ignore_num_lock() {
+    let mut h = Harness::new();
+    // Num Lock on: the keypad reports digits
+    h.send(key!(true, 79, LogicalKey::Character("1".into()), "1"));
+    h.send(key!(true, 117, LogicalKey::Character("=".into()), "="));
+    h.frame();
+    assert!(h.key_down(ImguiKey::Keypad1));
+    assert!(h.key_down(ImguiKey::KeypadEqual));
+    assert!(!h.key_down(ImguiKey::Alpha1));
+    assert!(!h.key_down(ImguiKey::Equal));
+
+    // Num Lock off: the same key reports navigation
+    h.send(key!(false, 79, LogicalKey::Character("1".into()), ""));
+    h.send(key!(true, 79, LogicalKey::Key(Key::NumpadEnd), ""));
+    h.frame();
+    assert!(h.key_down(ImguiKey::Keypad1));
+    assert!(!h.key_down(ImguiKey::End));
+}
+
+#[test]
+fn keypad_bindings_override_the_physical_default() {
+    let mut h = Harness::new();
+    h.platform().key_map_mut().merge(
+        "key NumpadEnd = End\nkey NumpadEnter = Enter"
+            .parse()
+            .unwrap(),
+    );
+    h.send(key!(true, 79, LogicalKey::Key(Key::NumpadEnd), ""));
+    h.send(key!(true, 96, LogicalKey::Key(Key::NumpadEnter), "\r"));
+    h.frame();
+    assert!(h.key_down(ImguiKey::End));
+    assert!(h.key_down(ImguiKey::Enter));
+    assert!(!h.key_down(ImguiKey::Keypad1));
+    assert!(!h.key_down(ImguiKey::KeypadEnter));
+
+    // with Num Lock on the key reports a digit, which has no named binding
+    h.send(key!(false, 79, LogicalKey::Key(Key::NumpadEnd), ""));
+    h.send(key!(true, 79, LogicalKey::Character("1".into()), "1"));
+    h.frame();
+    assert!(h.key_down(ImguiKey::Keypad1));
+}
+
+#[test]
+fn scroll_is_converted_to_wheel_steps() {
+    let mut h = Harness::new();
+    h.send(WindowEvent::Scroll {
+        direction: ScrollDirection::Vertical,
+        value: 26.0,
+    });
+    assert_eq!(h.wheel(), [0.0, 1.0]);
+
+    h.send(WindowEvent::Scroll {
+        direction: ScrollDirection::Horizontal,
+        value: -13.0,
+    });
+    assert_eq!(h.wheel(), [-0.5, 0.0]);
+}
+
+#[test]
+fn wheel_steps_follow_value120() {
+    let mut h = Harness::new();
+    h.scroll(ScrollEvent::Wheel {
+        axis: ScrollAxis::Vertical,
+        value120: 120,
+    });
+    assert_eq!(h.wheel(), [0.0, 1.0]);
+    // high-resolution wheels report parts of a detent
+    for _ in 0..3 {
+        h.scroll(ScrollEvent::Wheel {
+            axis: ScrollAxis::Horizontal,
+            value120: -20,
+        });
+    }
+    assert_eq!(h.wheel(), [-0.5, 0.0]);
+}
+
+#[test]
+fn scroll_sensitivity_and_natural_scrolling() {
+    let mut h = Harness::new();
+    h.platform().set_scroll_settings(ScrollSettings {
+        sensitivity: [2.0, 0.5],
+        ..ScrollSettings::default()
+    });
+    h.scroll(ScrollEvent::Wheel {
+        axis: ScrollAxis::Horizontal,
+        value120: 120,
+    });
+    h.scroll(ScrollEvent::Wheel {
+        axis: ScrollAxis::Vertical,
+        value120: 120,
+    });
+    assert_eq!(h.wheel(), [2.0, 0.5]);
+
+    h.platform().set_scroll_settings(ScrollSettings {
+        natural: true,
+        ..ScrollSettings::default()
+    });
+    h.scroll(ScrollEvent::Continuous {
+        axis: ScrollAxis::Vertical,
+        value: 26.0,
+        source: ScrollSource::Finger,
+    });
+    assert_eq!(h.wheel(), [0.0, -1.0]);
+}
+
+#[test]
+fn scroll_stop_drops_leftover_steps() {
+    let finger = |value| ScrollEvent::Continuous {
+        axis: ScrollAxis::Vertical,
+        value,
+        source: ScrollSource::Finger,
+    };
+    let mut h = Harness::new();
+    // 0.1 px is less than 1/120 of a step: two of them are carried over,
+    // but not past the end of the sequence
+    h.scroll(finger(0.1));
+    h.scroll(finger(0.1));
+    h.scroll(ScrollEvent::Stop {
+        axis: ScrollAxis::Vertical,
+    });
+    h.scroll(finger(0.1));
+    assert_eq!(h.wheel(), [0.0, 0.0]);
+
+    h.scroll(finger(0.1));
+    h.scroll(finger(0.1));
+    assert_eq!(h.wheel(), [0.0, 1.0 / 120.0]);
+}
+
+#[test]
+fn pointer_input_after_a_touch_comes_from_the_mouse() {
+    let buffer = SharedBuffer::default();
+    let mut h = Harness::new();
+    h.platform().start_recording(buffer.clone()).unwrap();
+    h.frame();
+    h.touch(TouchEvent::Down {
+        id: 0,
+        x: 10.0,
+        y: 10.0,
+    });
+    h.touch(TouchEvent::Up { id: 0 });
+    h.frame();
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Left,
+    });
+    h.frame();
+    h.platform().stop_recording().unwrap();
+
+    let recording: Recording = String::from_utf8(buffer.0.take()).unwrap().parse().unwrap();
+    let sources = |frame: usize| -> Vec<_> {
+        recording.frames[frame]
+            .events
+            .iter()
+            .filter_map(|(_, event)| match event {
+                InputEvent::MouseSource(source) => Some(*source),
+                _ => None,
+            })
+            .collect()
+    };
+    assert!(sources(1).contains(&MouseSource::TouchScreen));
+    assert_eq!(sources(2), [MouseSource::Mouse]);
+}
+
+#[test]
+fn barrel_buttons_map_to_right_and_middle() {
+    let mut h = Harness::new();
+    let down = |h: &Harness, button: MouseButton| h.io().mouse_down[button as usize];
+    h.pen(PenEvent::ProximityIn { tool: PenTool::Pen });
+    h.pen(PenEvent::Button {
+        button: 0,
+        down: true,
+    });
+    h.frame();
+    assert!(down(&h, MouseButton::Right));
+    assert!(!down(&h, MouseButton::Middle));
+
+    h.pen(PenEvent::Button {
+        button: 0,
+        down: false,
+    });
+    h.pen(PenEvent::Button {
+        button: 1,
+        down: true,
+    });
+    h.frame();
+    assert!(!down(&h, MouseButton::Right));
+    assert!(down(&h, MouseButton::Middle));
+}
+
+#[test]
+fn leaving_proximity_releases_the_pen() {
+    let mut h = Harness::new();
+    let down = |h: &Harness, button: MouseButton| h.io().mouse_down[button as usize];
+    h.pen(PenEvent::ProximityIn { tool: PenTool::Pen });
+    h.pen(PenEvent::Motion { x: 20.0, y: 30.0 });
+    h.pen(PenEvent::Down);
+    for button in [0, 1] {
+        h.pen(PenEvent::Button { button, down: true });
+    }
+    h.frame();
+    assert!(down(&h, MouseButton::Left));
+    assert!(down(&h, MouseButton::Right));
+    assert!(down(&h, MouseButton::Middle));
+
+    h.pen(PenEvent::ProximityOut);
+    h.frame();
+    assert!(!down(&h, MouseButton::Left));
+    assert!(!down(&h, MouseButton::Right));
+    assert!(!down(&h, MouseButton::Middle));
+    assert_eq!(h.io().mouse_pos, [-f32::MAX, -f32::MAX]);
+    assert_eq!(h.platform().pen(), None);
+}
+
+#[test]
+fn pen_input_comes_from_the_pen() {
+    let buffer = SharedBuffer::default();
+    let mut h = Harness::new();
+    h.platform().start_recording(buffer.clone()).unwrap();
+    h.frame();
+    h.pen(PenEvent::ProximityIn { tool: PenTool::Pen });
+    h.pen(PenEvent::Motion { x: 20.0, y: 30.0 });
+    h.pen(PenEvent::Down);
+    h.frame();
+    h.platform().stop_recording().unwrap();
+
+    let recording: Recording = String::from_utf8(buffer.0.take()).unwrap().parse().unwrap();
+    let sources: Vec<_> = recording.frames[1]
+        .events
+        .iter()
+        .filter_map(|(_, event)| match event {
+            InputEvent::MouseSource(source) => Some(*source),
+            _ => None,
+        })
+        .collect();
+    assert!(!sources.is_empty());
+    assert!(sources.iter().all(|source| *source == MouseSource::Pen));
+}
+
+#[test]
+fn pen_samples_are_collected_per_frame() {
+    let mut h = Harness::new();
+    h.pen(PenEvent::ProximityIn {
+        tool: PenTool::Eraser,
+    });
+    h.pen(PenEvent::Motion { x: 20.0, y: 30.0 });
+    h.pen(PenEvent::Pressure(0.25));
+    h.pen(PenEvent::Tilt { x: 10.0, y: -5.0 });
+    h.pen(PenEvent::Frame { time: 100 });
+    h.pen(PenEvent::Pressure(0.5));
+    h.pen(PenEvent::Frame { time: 108 });
+
+    let samples = h.platform().pen_samples().to_vec();
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].tool, PenTool::Eraser);
+    assert_eq!(samples[0].position, [20.0, 30.0]);
+    assert_eq!(samples[0].pressure, 0.25);
+    assert_eq!(samples[0].tilt, [10.0, -5.0]);
+    assert_eq!(samples[0].time, 100);
+    assert_eq!((samples[1].pressure, samples[1].time), (0.5, 108));
+    assert_eq!(h.platform().pen(), Some(samples[1]));
+
+    h.frame();
+    assert!(h.platform().pen_samples().is_empty());
+}
+
+#[test]
+fn two_finger_scroll_holds_back_the_first_press() {
+    let mut h = Harness::new();
+    h.platform().set_touch_settings(TouchSettings {
+        two_finger_scroll: true,
+        ..TouchSettings::default()
+    });
+    let left = MouseButton::Left as usize;
+
+    h.touch(TouchEvent::Down {
+        id: 0,
+        x: 10.0,
+        y: 10.0,
+    });
+    h.frame();
+    assert!(!h.io().mouse_down[left]);
+    h.touch(TouchEvent::Down {
+        id: 1,
+        x: 30.0,
+        y: 10.0,
+    });
+    h.touch(TouchEvent::Motion {
+        id: 1,
+        x: 30.0,
+        y: 62.0,
+    });
+    assert_eq!(h.wheel(), [0.0, 1.0]);
+    assert!(!h.io().mouse_down[left]);
+    h.touch(TouchEvent::Up { id: 1 });
+    h.touch(TouchEvent::Up { id: 0 });
+    h.frame();
+    assert!(!h.io().mouse_down[left]);
+
+    // a single finger presses once it moves
+    h.touch(TouchEvent::Down {
+        id: 2,
+        x: 10.0,
+        y: 10.0,
+    });
+    h.touch(TouchEvent::Motion {
+        id: 2,
+        x: 12.0,
+        y: 10.0,
+    });
+    h.frame();
+    assert!(h.io().mouse_down[left]);
+    assert_eq!(h.io().mouse_pos, [12.0, 10.0]);
+}
+
+#[test]
+fn cursor_shapes_are_only_advertised_with_a_callback() {
+    let mut h = Harness::new();
+    let flags = |h: &Harness| h.io().backend_flags;
+    assert!(!flags(&h).contains(BackendFlags::HAS_MOUSE_CURSORS));
+    h.with_platform(|platform, imgui| platform.set_cursor_callback(imgui, |_| {}));
+    assert!(flags(&h).contains(BackendFlags::HAS_MOUSE_CURSORS));
+}
+
+#[test]
+fn pointer_warps_are_only_advertised_with_a_callback() {
+    let mut h = Harness::new();
+    let flags = |h: &Harness| h.io().backend_flags;
+    assert!(!flags(&h).contains(BackendFlags::HAS_SET_MOUSE_POS));
+    h.with_platform(|platform, imgui| platform.set_warp_callback(imgui, |_, _| {}));
+    assert!(flags(&h).contains(BackendFlags::HAS_SET_MOUSE_POS));
+}
+
+#[test]
+fn focus_loss_releases_held_input() {
+    let mut h = Harness::new();
+    h.send(tab(true));
+    h.send(WindowEvent::PointerMoved(5.0, 5.0));
+    h.send(WindowEvent::PointerButton {
+        down: true,
+        button: PointerButton::Right,
+    });
+    h.frame();
+    assert!(h.io().mouse_down[MouseButton::Right as usize]);
+
+    h.send(WindowEvent::Focus(false));
+    h.frame();
+    assert!(!h.io().mouse_down[MouseButton::Right as usize]);
+    assert!(!h.key_down(ImguiKey::Tab));
+}
+
+#[test]
+fn resize_and_scale_update_the_display() {
+    let mut h = Harness::new();
+    assert_eq!(h.io().display_size, [800.0, 600.0]);
+    assert_eq!(h.io().display_framebuffer_scale, [1.0, 1.0]);
+
+    h.resize([1024.0, 768.0], 1.0);
+    assert_eq!(h.io().display_size, [1024.0, 768.0]);
+
+    h.resize([1024.0, 768.0], 1.5);
+    assert_eq!(h.io().display_size, [1024.0, 768.0]);
+    assert_eq!(h.io().display_framebuffer_scale, [1.5, 1.5]);
+    assert_eq!(h.platform().scale_factor(), 1.5);
+}
+
+#[test]
+fn frame_times_follow_the_time_source() {
+    let mut h = Harness::new();
+    let now = Rc::new(Cell::new(Duration::from_secs(10)));
+    let source = now.clone();
+    h.platform().set_time_source(move || source.get());
+    let advance = |h: &mut Harness, by: Duration| {
+        now.set(now.get() + by);
+        h.frame();
+        h.io().delta_time
+    };
+
+    // the first frame after a new source has no previous one to measure from
+    assert_eq!(
+        advance(&mut h, Duration::ZERO),
+        (Duration::from_secs(1) / 60).as_secs_f32()
+    );
+    assert_eq!(advance(&mut h, Duration::from_millis(20)), 0.02);
+    // a stall, e.g. a suspend, is clamped to 250 ms
+    assert_eq!(advance(&mut h, Duration::from_secs(30)), 0.25);
+
+    h.platform().set_max_frame_delta(Duration::from_millis(100));
+    assert_eq!(advance(&mut h, Duration::from_secs(1)), 0.1);
+}
+
+#[test]
+fn style_sizes_scale_from_the_unscaled_style() {
+    let mut h = Harness::new();
+    let base = *h.imgui().style();
+    h.with_platform(|platform, imgui| {
+        platform.set_scale_policy(
+            imgui,
+            ScalePolicy {
+                scale_style: true,
+                ..ScalePolicy::default()
+            },
+        )
+    });
+    for scale in [1.3, 1.0] {
+        h.resize([800.0, 600.0], scale);
+    }
+    assert_eq!(h.imgui().style().window_padding, base.window_padding);
+    assert_eq!(h.imgui().style().grab_min_size, base.grab_min_size);
+}
+
+#[test]
+fn font_builders_run_when_the_policy_changes() {
+    let mut h = Harness::new();
+    h.resize([800.0, 600.0], 2.0);
+    let builds = Rc::new(Cell::new(0));
+    let builder = |builds: &Rc<Cell<u32>>| -> FontBuilder {
+        let builds = builds.clone();
+        Box::new(move |fonts, scale| {
+            builds.set(builds.get() + 1);
+            fonts.add_font(&[FontSource::DefaultFontData {
+                config: Some(FontConfig {
+                    size_pixels: 13.0 * scale,
+                    ..FontConfig::default()
+                }),
+            }]);
+        })
+    };
+    let policy = |builder| ScalePolicy {
+        rebuild_fonts: Some(builder),
+        ..ScalePolicy::default()
+    };
+
+    h.with_platform(|platform, imgui| platform.set_scale_policy(imgui, policy(builder(&builds))));
+    assert_eq!(builds.get(), 1);
+    assert_eq!(h.io().font_global_scale, 0.5);
+    // a new builder at the same scale still runs
+    h.with_platform(|platform, imgui| platform.set_scale_policy(imgui, policy(builder(&builds))));
+    assert_eq!(builds.get(), 2);
+
+    h.with_platform(|platform, imgui| platform.set_scale_policy(imgui, ScalePolicy::default()));
+    assert_eq!(h.io().font_global_scale, 1.0);
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn recordings_replay_the_same_input() {
+    let buffer = SharedBuffer::default();
+    let mut h = Harness::new();
+    h.platform().start_recording(buffer.clone()).unwrap();
+    h.frame();
+    h.send(WindowEvent::PointerMoved(30.0, 40.0));
+    h.send(tab(true));
+    h.frame();
+    h.platform().stop_recording().unwrap();
+
+    let text = String::from_utf8(buffer.0.take()).unwrap();
+    let recording: Recording = text.parse().unwrap();
+    assert_eq!(recording.frames.len(), 2);
+    let events: Vec<_> = recording.frames[1]
+        .events
+        .iter()
+        .map(|(_, event)| *event)
+        .collect();
+    assert_eq!(
+        events,
+        [
+            InputEvent::MouseSource(MouseSource::Mouse),
+            InputEvent::MousePos([30.0, 40.0]),
+            InputEvent::Key {
+                key: ImguiKey::Tab,
+                down: true,
+            },
+        ]
+    );
+    assert_eq!(recording.to_string(), text);
+    drop(h);
+
+    let mut h = Harness::new();
+    let mut replay = Replay::new(recording);
+    while replay.prepare_frame(h.imgui()) {
+        h.imgui().new_frame();
+        h.imgui().render();
+    }
+    assert_eq!(replay.frame(), Some(1));
+    assert_eq!(h.io().mouse_pos, [30.0, 40.0]);
+    assert!(h.key_down(ImguiKey::Tab));
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "docking")]
+
+use imgui::{BackendFlags, Condition, Context, Id, MouseButton, Ui};
+use imgui_waywin_support::{HeadlessWindow, PlatformWindow, ViewportHost, WaywinPlatform};
+use std::sync::{Arc, Mutex, MutexGuard};
+use waywin::event::{PointerButton, WindowEvent};
+
+/// imgui allows only one active context at a time, so tests take turns.
+static CONTEXT: Mutex<()> = Mutex::new(());
+
+/// A [`HeadlessWindow`] the test can resize while the platform holds it.
+struct SharedWindow(Mutex<HeadlessWindow>);
+impl SharedWindow {
+    fn set(&self, logical_size: [f32; 2], scale: f32) {
+        *self.0.lock().unwrap() = HeadlessWindow::new(logical_size, scale);
+    }
+}
+impl PlatformWindow for SharedWindow {
+    fn logical_size(&self) -> [f32; 2] {
+        self.0.lock().unwrap().logical_size()
+    }
+
+    fn physical_size(&self) -> [f32; 2] {
+        self.0.lock().unwrap().physical_size()
+    }
+
+    fn scale(&self) -> f32 {
+        self.0.lock().unwrap().scale()
+    }
+}
+
+struct Host;
+impl ViewportHost for Host {
+    type Window = SharedWindow;
+
+    fn create_window(&mut self) -> Option<Arc<SharedWindow>> {
+        let window = HeadlessWindow::new([120.0, 80.0], 1.0);
+        Some(Arc::new(SharedWindow(Mutex::new(window))))
+    }
+}
+
+struct Harness {
+    imgui: Context,
+    platform: WaywinPlatform,
+    window: HeadlessWindow,
+    open: bool,
+    _guard: MutexGuard<'static, ()>,
+}
+impl Harness {
+    /// Runs frames until the "popout" window, placed right of the main
+    /// window, has its own viewport.
+    fn new() -> (Self, Id) {
+        let guard = CONTEXT.lock().unwrap_or_else(|err| err.into_inner());
+        let mut imgui = Context::create();
+        imgui.set_ini_filename(None);
+        imgui.fonts().build_rgba32_texture();
+        imgui.io_mut().config_input_trickle_event_queue = false;
+        let window = HeadlessWindow::new([800.0, 600.0], 1.0);
+        let mut platform = WaywinPlatform::new(&mut imgui, &window);
+        platform.enable_viewports(&mut imgui, &window, Host);
+        // stands in for a renderer that draws the viewports
+        imgui
+            .io_mut()
+            .backend_flags
+            .insert(BackendFlags::RENDERER_HAS_VIEWPORTS);
+        let mut h = Self {
+            imgui,
+            platform,
+            window,
+            open: true,
+            _guard: guard,
+        };
+        for _ in 0..3 {
+            h.frame();
+        }
+        let main = h.imgui.main_viewport().id;
+        let viewport = h
+            .imgui
+            .viewports()
+            .map(|viewport| viewport.id)
+            .find(|id| *id != main)
+            .expect("no viewport for the popout");
+        (h, viewport)
+    }
+
+    fn frame(&mut self) {
+        self.platform.prepare_frame(&mut self.imgui, &self.window);
+        let ui = self.imgui.new_frame();
+        popout(ui, &mut self.open);
+        self.platform.prepare_render(ui, &self.window);
+        self.imgui.render();
+        self.imgui.update_platform_windows();
+    }
+
+    fn send(&mut self, viewport: Id, event: WindowEvent) {
+        self.platform
+            .handle_viewport_event(&mut self.imgui, viewport, event);
+    }
+
+    fn viewport_window(&self, viewport: Id) -> Arc<SharedWindow> {
+        self.platform.viewport_window(viewport).unwrap()
+    }
+}
+
+fn popout(ui: &Ui, open: &mut bool) {
+    if !*open {
+        return;
+    }
+    ui.window("popout")
+        .position([900.0, 100.0], Condition::FirstUseEver)
+        .size([120.0, 80.0], Condition::FirstUseEver)
+        .opened(open)
+        .build(|| ui.text("outside"));
+}
+
+#[test]
+fn resizes_are_requested_from_the_viewport_window() {
+    let (mut h, viewport) = Harness::new();
+    h.viewport_window(viewport).set([300.0, 200.0], 1.0);
+    h.send(viewport, WindowEvent::Resized);
+    h.frame();
+    let size = h.imgui.viewport_by_id(viewport).unwrap().size;
+    assert_eq!(size, [300.0, 200.0]);
+}
+
+#[test]
+fn scale_changes_reach_the_viewport() {
+    let (mut h, viewport) = Harness::new();
+    h.viewport_window(viewport).set([120.0, 80.0], 2.0);
+    h.send(viewport, WindowEvent::NewScaleFactor);
+    h.frame();
+    assert_eq!(h.imgui.viewport_by_id(viewport).unwrap().dpi_scale, 2.0);
+}
+
+#[test]
+fn close_requests_close_the_window() {
+    let (mut h, viewport) = Harness::new();
+    h.send(viewport, WindowEvent::Close);
+    h.frame();
+    assert!(!h.open);
+}
+
+#[test]
+fn focus_loss_releases_input_held_in_the_viewport() {
+    let (mut h, viewport) = Harness::new();
+    h.send(viewport, WindowEvent::Focus(true));
+    h.send(viewport, WindowEvent::PointerMoved(10.0, 30.0));
+    h.send(
+        viewport,
+        WindowEvent::PointerButton {
+            down: true,
+            button: PointerButton::Left,
+        },
+    );
+    h.frame();
+    assert!(h.imgui.io().mouse_down[MouseButton::Left as usize]);
+    // positions are relative to the viewport window
+    let [x, y] = h.imgui.viewport_by_id(viewport).unwrap().pos;
+    assert_eq!(h.imgui.io().mouse_pos, [x + 10.0, y + 30.0]);
+
+    h.send(viewport, WindowEvent::Focus(false));
+    h.frame();
+    assert!(!h.imgui.io().mouse_down[MouseButton::Left as usize]);
+}